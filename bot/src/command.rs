@@ -1,7 +1,9 @@
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     RotateLeft,
     RotateRight,
     Fire,
+    Thrust,
+    Teleport,
     Nothing,
 }
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crossover::CrossoverOperator, fitness::FitnessModel, logic::SelectionStrategy,
+    network::Mutation, Bot,
+};
+
+/// Breeding needs a survivor and at least one child, so anything smaller can't evolve.
+pub const MIN_POPULATION_SIZE: usize = 2;
+
+/// How a [`Bot`] evolves. Start from [`BotConfig::new`] and change what's needed.
+///
+/// Settings missing from a saved config keep their defaults, so checkpoints saved before a
+/// setting was added still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotConfig {
+    pub population_size: usize,
+    /// Share of the population that survives on merit.
+    pub graded_retain_percent: f32,
+    /// Share of the population that survives regardless of score, to keep some variety.
+    pub nongraded_retain_percent: f32,
+    /// Chance that a child mutates at all.
+    pub mutation_chance: f32,
+    /// How a mutating child's weights change.
+    pub mutation: Mutation,
+    pub selection_strategy: SelectionStrategy,
+    pub crossover: CrossoverOperator,
+    /// How an individual's games are turned into its score.
+    pub fitness: FitnessModel,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 8,
+            graded_retain_percent: 0.3,
+            nongraded_retain_percent: 0.2,
+            mutation_chance: 0.25,
+            mutation: Mutation::default(),
+            selection_strategy: SelectionStrategy::default(),
+            crossover: CrossoverOperator::default(),
+            fitness: FitnessModel::default(),
+        }
+    }
+}
+
+impl BotConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn population_size(mut self, population_size: usize) -> Self {
+        self.population_size = population_size;
+        self
+    }
+
+    pub fn graded_retain_percent(mut self, graded_retain_percent: f32) -> Self {
+        self.graded_retain_percent = graded_retain_percent;
+        self
+    }
+
+    pub fn nongraded_retain_percent(mut self, nongraded_retain_percent: f32) -> Self {
+        self.nongraded_retain_percent = nongraded_retain_percent;
+        self
+    }
+
+    pub fn mutation_chance(mut self, mutation_chance: f32) -> Self {
+        self.mutation_chance = mutation_chance;
+        self
+    }
+
+    /// Chance that each weight of a mutating child is nudged.
+    pub fn weight_mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation.rate = rate;
+        self
+    }
+
+    /// Standard deviation of the nudge given to a mutated weight.
+    pub fn weight_mutation_strength(mut self, strength: f32) -> Self {
+        self.mutation.strength = strength;
+        self
+    }
+
+    pub fn selection_strategy(mut self, selection_strategy: SelectionStrategy) -> Self {
+        self.selection_strategy = selection_strategy;
+        self
+    }
+
+    pub fn crossover(mut self, crossover: CrossoverOperator) -> Self {
+        self.crossover = crossover;
+        self
+    }
+
+    pub fn fitness(mut self, fitness: FitnessModel) -> Self {
+        self.fitness = fitness;
+        self
+    }
+
+    /// Creates the bot, which evolves the same way every time for a given seed.
+    pub fn build(self, seed: u64) -> eyre::Result<Bot> {
+        self.validate()?;
+
+        Ok(Bot::from_config(self, seed))
+    }
+
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.population_size < MIN_POPULATION_SIZE {
+            eyre::bail!(
+                "a population of {} is too small, it needs at least {MIN_POPULATION_SIZE}",
+                self.population_size
+            );
+        }
+
+        let shares = [
+            ("graded retain percent", self.graded_retain_percent.into()),
+            (
+                "nongraded retain percent",
+                self.nongraded_retain_percent.into(),
+            ),
+            ("mutation chance", self.mutation_chance.into()),
+            ("weight mutation rate", self.mutation.rate),
+        ];
+        for (name, share) in shares {
+            if !(0.0..=1.0).contains(&share) {
+                eyre::bail!("the {name} is {share}, it has to be between 0 and 1");
+            }
+        }
+
+        for (name, weight) in self.fitness.weights() {
+            if !weight.is_finite() {
+                eyre::bail!("the {name} fitness weight is {weight}, it has to be a finite number");
+            }
+        }
+
+        let strength = self.mutation.strength;
+        if !strength.is_finite() || strength < 0.0 {
+            eyre::bail!("the weight mutation strength is {strength}, it can't be negative");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_saved_before_newer_settings_load_with_defaults() {
+        let saved = "(
+            population_size: 12,
+            graded_retain_percent: 0.4,
+            nongraded_retain_percent: 0.1,
+            mutation_chance: 0.5,
+            mutation: (rate: 0.2, strength: 0.3),
+            selection_strategy: Rank,
+        )";
+        let config: BotConfig = ron::from_str(saved).unwrap();
+
+        assert_eq!(config.population_size, 12);
+        assert_eq!(config.selection_strategy, SelectionStrategy::Rank);
+        assert_eq!(config.crossover, CrossoverOperator::default());
+        assert_eq!(config.fitness, FitnessModel::default());
+    }
+
+    #[test]
+    fn the_defaults_are_valid() {
+        assert!(BotConfig::new().build(0).is_ok());
+    }
+
+    #[test]
+    fn shares_outside_zero_to_one_are_rejected() {
+        for share in [-0.1, 1.5, f32::NAN] {
+            let configs = [
+                BotConfig::new().graded_retain_percent(share),
+                BotConfig::new().nongraded_retain_percent(share),
+                BotConfig::new().mutation_chance(share),
+                BotConfig::new().weight_mutation_rate(share.into()),
+            ];
+
+            for config in configs {
+                assert!(config.clone().build(0).is_err(), "{config:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn shares_of_zero_and_one_are_allowed() {
+        for share in [0.0, 1.0] {
+            let config = BotConfig::new()
+                .graded_retain_percent(share)
+                .nongraded_retain_percent(share)
+                .mutation_chance(share)
+                .weight_mutation_rate(share.into());
+
+            assert!(config.build(0).is_ok());
+        }
+    }
+
+    #[test]
+    fn fitness_weights_have_to_be_finite() {
+        for weight in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let fitness = FitnessModel {
+                aim: weight,
+                ..Default::default()
+            };
+
+            assert!(BotConfig::new().fitness(fitness).build(0).is_err());
+        }
+    }
+
+    #[test]
+    fn negative_mutation_strengths_are_rejected() {
+        for strength in [-1.0, f32::INFINITY] {
+            assert!(BotConfig::new()
+                .weight_mutation_strength(strength)
+                .build(0)
+                .is_err());
+        }
+    }
+}
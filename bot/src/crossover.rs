@@ -0,0 +1,265 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Combines two parents' weights into a child's.
+pub trait Crossover {
+    /// `parent_one` and `parent_two` must be the same length, and so is the child. `scores` are the
+    /// parents' fitness, in the same order.
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32>;
+}
+
+/// Parent one's weights up to a random cut, then parent two's.
+#[derive(Debug, Clone, Copy)]
+pub struct SinglePoint;
+
+impl Crossover for SinglePoint {
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        _scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        let cut = rng.gen_range(0..=parent_one.len());
+
+        [&parent_one[..cut], &parent_two[cut..]].concat()
+    }
+}
+
+/// Each weight comes from either parent, as if by coin toss.
+#[derive(Debug, Clone, Copy)]
+pub struct Uniform;
+
+impl Crossover for Uniform {
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        _scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        parent_one
+            .iter()
+            .zip(parent_two)
+            .map(|(&one, &two)| if rng.gen_bool(0.5) { one } else { two })
+            .collect()
+    }
+}
+
+/// Each weight is a random mix of both parents' weights, somewhere between the two.
+#[derive(Debug, Clone, Copy)]
+pub struct Blend;
+
+impl Crossover for Blend {
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        _scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        parent_one
+            .iter()
+            .zip(parent_two)
+            .map(|(&one, &two)| {
+                let mix: f32 = rng.gen_range(0.0..=1.0);
+
+                one * mix + two * (1.0 - mix)
+            })
+            .collect()
+    }
+}
+
+/// Each weight comes from either parent, favouring the fitter one in proportion to its score.
+#[derive(Debug, Clone, Copy)]
+pub struct FitnessWeighted;
+
+impl Crossover for FitnessWeighted {
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        let [score_one, score_two] = scores.map(|score| score.max(0.0));
+        let parent_one_chance = if score_one + score_two > 0.0 {
+            score_one / (score_one + score_two)
+        } else {
+            0.5
+        };
+
+        parent_one
+            .iter()
+            .zip(parent_two)
+            .map(|(&one, &two)| {
+                if rng.gen_bool(parent_one_chance.into()) {
+                    one
+                } else {
+                    two
+                }
+            })
+            .collect()
+    }
+}
+
+/// Picks one of the crossovers above, so that it can be configured and saved with a bot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrossoverOperator {
+    #[default]
+    SinglePoint,
+    Uniform,
+    Blend,
+    FitnessWeighted,
+}
+
+impl Crossover for CrossoverOperator {
+    fn crossover(
+        &self,
+        parent_one: &[f32],
+        parent_two: &[f32],
+        scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        match self {
+            CrossoverOperator::SinglePoint => {
+                SinglePoint.crossover(parent_one, parent_two, scores, rng)
+            }
+            CrossoverOperator::Uniform => Uniform.crossover(parent_one, parent_two, scores, rng),
+            CrossoverOperator::Blend => Blend.crossover(parent_one, parent_two, scores, rng),
+            CrossoverOperator::FitnessWeighted => {
+                FitnessWeighted.crossover(parent_one, parent_two, scores, rng)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    const OPERATORS: [CrossoverOperator; 4] = [
+        CrossoverOperator::SinglePoint,
+        CrossoverOperator::Uniform,
+        CrossoverOperator::Blend,
+        CrossoverOperator::FitnessWeighted,
+    ];
+
+    /// Parent one's weights are all positive and parent two's all negative, so every child weight
+    /// gives away where it came from.
+    fn parents() -> (Vec<f32>, Vec<f32>) {
+        let parent_one = (1..=20).map(|weight| weight as f32).collect();
+        let parent_two = (1..=20).map(|weight| -weight as f32).collect();
+
+        (parent_one, parent_two)
+    }
+
+    fn from_either_parent(child: &[f32], parent_one: &[f32], parent_two: &[f32]) -> bool {
+        child
+            .iter()
+            .zip(parent_one.iter().zip(parent_two))
+            .all(|(weight, (one, two))| weight == one || weight == two)
+    }
+
+    #[test]
+    fn children_are_as_long_as_their_parents() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+
+        for operator in OPERATORS {
+            for _ in 0..100 {
+                let child = operator.crossover(&parent_one, &parent_two, [1.0, 2.0], &mut rng);
+
+                assert_eq!(child.len(), parent_one.len(), "{operator:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn single_point_takes_parent_one_then_parent_two() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+
+        for _ in 0..100 {
+            let child = SinglePoint.crossover(&parent_one, &parent_two, [0.0, 0.0], &mut rng);
+            let cut = child
+                .iter()
+                .position(|weight| weight.is_sign_negative())
+                .unwrap_or(child.len());
+
+            assert_eq!(child[..cut], parent_one[..cut]);
+            assert_eq!(child[cut..], parent_two[cut..]);
+        }
+    }
+
+    #[test]
+    fn single_point_cuts_in_different_places() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+        let first = SinglePoint.crossover(&parent_one, &parent_two, [0.0, 0.0], &mut rng);
+
+        assert!((0..100).any(|_| {
+            SinglePoint.crossover(&parent_one, &parent_two, [0.0, 0.0], &mut rng) != first
+        }));
+    }
+
+    #[test]
+    fn uniform_takes_each_weight_from_a_parent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+        let child = Uniform.crossover(&parent_one, &parent_two, [0.0, 0.0], &mut rng);
+
+        assert!(from_either_parent(&child, &parent_one, &parent_two));
+        assert!(child.iter().any(|weight| weight.is_sign_positive()));
+        assert!(child.iter().any(|weight| weight.is_sign_negative()));
+    }
+
+    #[test]
+    fn blend_keeps_each_weight_between_the_parents() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+        let child = Blend.crossover(&parent_one, &parent_two, [0.0, 0.0], &mut rng);
+
+        for ((weight, one), two) in child.iter().zip(&parent_one).zip(&parent_two) {
+            assert!(
+                (two..=one).contains(&weight),
+                "{weight} isn't in {two}..={one}"
+            );
+        }
+    }
+
+    #[test]
+    fn fitness_weighted_only_takes_from_a_fit_parent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+
+        let child = FitnessWeighted.crossover(&parent_one, &parent_two, [1.0, 0.0], &mut rng);
+        assert_eq!(child, parent_one);
+
+        let child = FitnessWeighted.crossover(&parent_one, &parent_two, [0.0, 1.0], &mut rng);
+        assert_eq!(child, parent_two);
+    }
+
+    #[test]
+    fn fitness_weighted_favours_the_fitter_parent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (parent_one, parent_two) = parents();
+        let child = FitnessWeighted.crossover(&parent_one, &parent_two, [9.0, 1.0], &mut rng);
+        let from_parent_one = child
+            .iter()
+            .filter(|weight| weight.is_sign_positive())
+            .count();
+
+        assert!(from_either_parent(&child, &parent_one, &parent_two));
+        assert!(from_parent_one > child.len() / 2);
+    }
+}
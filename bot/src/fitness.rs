@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+/// What an individual did during one game, gathered by the game as it plays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayStats {
+    pub asteroids_destroyed: u32,
+    /// Points earned from every `ScoreEvent`.
+    pub points: u32,
+    /// Seconds spent flying the ship.
+    pub survival_time: f32,
+    pub shots_fired: u32,
+    /// Shots that destroyed an asteroid or a UFO.
+    pub hits: u32,
+    pub deaths: u32,
+    /// How close a bullet came to the target, from 0 to 1 for a hit, at best.
+    pub best_aim: f32,
+}
+
+impl PlayStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots_fired as f32
+        }
+    }
+}
+
+/// How much each of the [`PlayStats`] counts towards an individual's fitness.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FitnessModel {
+    pub asteroids_destroyed: f32,
+    pub points: f32,
+    /// Per second survived.
+    pub survival_time: f32,
+    pub accuracy: f32,
+    /// Taken off for every death.
+    pub death_penalty: f32,
+    /// Rewards aiming near the target, which gets a new population started before it can hit
+    /// anything.
+    pub aim: f32,
+}
+
+impl Default for FitnessModel {
+    fn default() -> Self {
+        Self {
+            asteroids_destroyed: 1.0,
+            points: 0.002,
+            survival_time: 0.02,
+            accuracy: 2.0,
+            death_penalty: 1.0,
+            aim: 1.0,
+        }
+    }
+}
+
+impl FitnessModel {
+    /// Each stat times its weight, all added up, with the death penalty taken off for every
+    /// death.
+    pub fn fitness(&self, stats: &PlayStats) -> f32 {
+        self.asteroids_destroyed * stats.asteroids_destroyed as f32
+            + self.points * stats.points as f32
+            + self.survival_time * stats.survival_time
+            + self.accuracy * stats.accuracy()
+            - self.death_penalty * stats.deaths as f32
+            + self.aim * stats.best_aim
+    }
+
+    /// Every weight along with its name.
+    pub(crate) fn weights(&self) -> [(&'static str, f32); 6] {
+        [
+            ("asteroids destroyed", self.asteroids_destroyed),
+            ("points", self.points),
+            ("survival time", self.survival_time),
+            ("accuracy", self.accuracy),
+            ("death penalty", self.death_penalty),
+            ("aim", self.aim),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accuracy_is_zero_without_shots() {
+        let stats = PlayStats {
+            hits: 0,
+            shots_fired: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.accuracy(), 0.0);
+    }
+
+    #[test]
+    fn accuracy_is_hits_per_shot() {
+        let stats = PlayStats {
+            hits: 3,
+            shots_fired: 12,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.accuracy(), 0.25);
+    }
+
+    #[test]
+    fn fitness_adds_up_the_weighted_stats() {
+        let model = FitnessModel {
+            asteroids_destroyed: 1.0,
+            points: 0.01,
+            survival_time: 0.5,
+            accuracy: 2.0,
+            death_penalty: 3.0,
+            aim: 4.0,
+        };
+        let stats = PlayStats {
+            asteroids_destroyed: 5,
+            points: 200,
+            survival_time: 10.0,
+            shots_fired: 4,
+            hits: 2,
+            deaths: 1,
+            best_aim: 0.5,
+        };
+
+        // 5 + 2 + 5 + 1 - 3 + 2
+        assert_eq!(model.fitness(&stats), 12.0);
+    }
+
+    #[test]
+    fn each_weight_only_counts_its_own_stat() {
+        let stats = PlayStats {
+            asteroids_destroyed: 2,
+            points: 300,
+            survival_time: 7.0,
+            shots_fired: 4,
+            hits: 1,
+            deaths: 2,
+            best_aim: 0.8,
+        };
+        let only = |model: FitnessModel| model.fitness(&stats);
+        let none = FitnessModel {
+            asteroids_destroyed: 0.0,
+            points: 0.0,
+            survival_time: 0.0,
+            accuracy: 0.0,
+            death_penalty: 0.0,
+            aim: 0.0,
+        };
+
+        assert_eq!(only(none), 0.0);
+        assert_eq!(
+            only(FitnessModel {
+                asteroids_destroyed: 1.0,
+                ..none
+            }),
+            2.0
+        );
+        assert_eq!(
+            only(FitnessModel {
+                points: 1.0,
+                ..none
+            }),
+            300.0
+        );
+        assert_eq!(
+            only(FitnessModel {
+                survival_time: 1.0,
+                ..none
+            }),
+            7.0
+        );
+        assert_eq!(
+            only(FitnessModel {
+                accuracy: 1.0,
+                ..none
+            }),
+            0.25
+        );
+        assert_eq!(
+            only(FitnessModel {
+                death_penalty: 1.0,
+                ..none
+            }),
+            -2.0
+        );
+        assert_eq!(only(FitnessModel { aim: 1.0, ..none }), 0.8);
+    }
+}
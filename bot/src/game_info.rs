@@ -1,5 +1,48 @@
 use glam::Vec2;
 
+/// How many of the nearest hazards a bot can see at once.
+pub const SENSOR_COUNT: usize = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HazardKind {
+    /// An empty sensor slot, when there are fewer hazards than sensors.
+    #[default]
+    None,
+    Asteroid,
+    Ufo,
+    UfoBullet,
+}
+
+/// Something that can kill the ship, as seen from the ship.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    /// Where the hazard is relative to the ship, taking the shortest way around the screen edges.
+    pub offset: Vec2,
+    /// How the hazard is moving relative to the ship, so a hazard flying alongside is still.
+    pub velocity: Vec2,
+    pub size: f32,
+}
+
+impl Hazard {
+    pub fn new(
+        kind: HazardKind,
+        ship_position: Vec2,
+        ship_velocity: Vec2,
+        position: Vec2,
+        velocity: Vec2,
+        size: f32,
+        arena_size: Vec2,
+    ) -> Self {
+        Self {
+            kind,
+            offset: wrapped_offset(ship_position, position, arena_size),
+            velocity: velocity - ship_velocity,
+            size,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GameInfo {
     pub position: Vec2,
@@ -10,9 +53,13 @@ pub struct GameInfo {
     pub bullet_position: Option<Vec2>,
     pub bullet_speed: f32,
     pub aim_rotation: f32,
+    /// The nearest hazards, closest first.
+    pub hazards: [Hazard; SENSOR_COUNT],
 }
 
 impl GameInfo {
+    /// Only the [`SENSOR_COUNT`] nearest of `hazards` are kept.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         position: Vec2,
         arena_size: Vec2,
@@ -22,7 +69,16 @@ impl GameInfo {
         bullet_position: Option<Vec2>,
         bullet_speed: f32,
         aim_rotation: f32,
+        hazards: impl IntoIterator<Item = Hazard>,
     ) -> Self {
+        let mut nearest = hazards.into_iter().collect::<Vec<_>>();
+        nearest.sort_by(|a, b| a.offset.length().total_cmp(&b.offset.length()));
+
+        let mut hazards = [Hazard::default(); SENSOR_COUNT];
+        for (sensor, hazard) in hazards.iter_mut().zip(nearest) {
+            *sensor = hazard;
+        }
+
         Self {
             position,
             arena_size,
@@ -32,6 +88,87 @@ impl GameInfo {
             bullet_position,
             bullet_speed,
             aim_rotation,
+            hazards,
         }
     }
 }
+
+/// The shortest offset from `from` to `to` in an arena whose edges wrap around.
+pub fn wrapped_offset(from: Vec2, to: Vec2, arena_size: Vec2) -> Vec2 {
+    let offset = to - from;
+
+    offset - arena_size * (offset / arena_size).round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARENA_SIZE: Vec2 = Vec2::new(1920., 1080.);
+
+    #[test]
+    fn offset_goes_straight_when_that_is_shortest() {
+        let offset = wrapped_offset(Vec2::new(-100., 50.), Vec2::new(200., -50.), ARENA_SIZE);
+
+        assert_eq!(offset, Vec2::new(300., -100.));
+    }
+
+    #[test]
+    fn offset_wraps_across_each_edge() {
+        let edges = [
+            // right edge to left edge
+            (
+                Vec2::new(900., 0.),
+                Vec2::new(-900., 0.),
+                Vec2::new(120., 0.),
+            ),
+            // left edge to right edge
+            (
+                Vec2::new(-900., 0.),
+                Vec2::new(900., 0.),
+                Vec2::new(-120., 0.),
+            ),
+            // top edge to bottom edge
+            (
+                Vec2::new(0., 500.),
+                Vec2::new(0., -500.),
+                Vec2::new(0., 80.),
+            ),
+            // bottom edge to top edge
+            (
+                Vec2::new(0., -500.),
+                Vec2::new(0., 500.),
+                Vec2::new(0., -80.),
+            ),
+            // across a corner
+            (
+                Vec2::new(900., 500.),
+                Vec2::new(-900., -500.),
+                Vec2::new(120., 80.),
+            ),
+        ];
+
+        for (from, to, expected) in edges {
+            assert_eq!(
+                wrapped_offset(from, to, ARENA_SIZE),
+                expected,
+                "{from} to {to}"
+            );
+        }
+    }
+
+    #[test]
+    fn hazard_velocity_is_relative_to_the_ship() {
+        let hazard = Hazard::new(
+            HazardKind::Asteroid,
+            Vec2::ZERO,
+            Vec2::new(30., -10.),
+            Vec2::new(100., 100.),
+            Vec2::new(50., 20.),
+            40.,
+            ARENA_SIZE,
+        );
+
+        assert_eq!(hazard.velocity, Vec2::new(20., 30.));
+    }
+}
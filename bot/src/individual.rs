@@ -1,106 +1,179 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, path::Path};
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::{command::Command, game_info::GameInfo, perceptron::Perceptron};
+use crate::{
+    command::Command,
+    crossover::Crossover,
+    fitness::PlayStats,
+    game_info::{GameInfo, HazardKind, SENSOR_COUNT},
+    network::{Activation, Mutation, Network},
+};
 
-#[derive(Debug, Clone)]
+/// Number of values [`Individual::play`] feeds its brain: the ship's position and rotation, then
+/// for each sensor the hazard's offset, velocity, size and which kind it is.
+const INPUT_COUNT: usize = 3 + SENSOR_COUNT * 8;
+/// One output each for rotating, firing, thrusting and teleporting.
+const OUTPUT_COUNT: usize = 4;
+const HIDDEN_LAYERS: [usize; 1] = [8];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Individual {
-    rotate_perceptron: Perceptron,
-    fire_perceptron: Perceptron,
+    brain: Network,
     pub score: f32,
+    /// What happened in the game being played right now.
+    #[serde(skip)]
+    pub stats: PlayStats,
 }
 
 impl Individual {
-    pub fn new(rng: &mut ThreadRng) -> Self {
-        let rotate_perceptron = Perceptron::new(rng, 7);
-        let fire_perceptron = Perceptron::new(rng, 7);
+    pub fn new(rng: &mut impl Rng) -> Self {
+        Self::with_brain(rng, &HIDDEN_LAYERS, Activation::Tanh)
+    }
+
+    /// Creates an individual whose brain has a hidden layer of each given size, all using
+    /// `activation`.
+    pub fn with_brain(rng: &mut impl Rng, hidden_layers: &[usize], activation: Activation) -> Self {
+        let layer_sizes = [&[INPUT_COUNT], hidden_layers, &[OUTPUT_COUNT]].concat();
+        // the outputs are read as -1..1, whatever the hidden layers use
+        let brain = Network::new(rng, &layer_sizes, activation, Activation::Tanh);
         let score = 0.0;
 
         Self {
-            rotate_perceptron,
-            fire_perceptron,
+            brain,
             score,
+            stats: PlayStats::default(),
         }
     }
 
     pub fn new_from_parents(
         parent_one: &Self,
         parent_two: &Self,
-        mutation_chance: f32,
-        rng: &mut ThreadRng,
+        crossover: &impl Crossover,
+        rng: &mut impl Rng,
     ) -> Self {
-        let rotate_perceptron = Perceptron::new_from_parents(
-            &parent_one.rotate_perceptron,
-            &parent_two.rotate_perceptron,
+        let brain = Network::new_from_parents(
+            &parent_one.brain,
+            &parent_two.brain,
+            crossover,
+            [parent_one.score, parent_two.score],
+            rng,
         );
-        let fire_perceptron =
-            Perceptron::new_from_parents(&parent_one.fire_perceptron, &parent_two.fire_perceptron);
-        let mut individual = Self {
-            rotate_perceptron,
-            fire_perceptron,
+
+        Self {
+            brain,
             score: 0.0,
-        };
+            stats: PlayStats::default(),
+        }
+    }
+
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let individual: Self = ron::from_str(&std::fs::read_to_string(path)?)?;
 
-        if rng.gen_range(0.0..=1.0) < mutation_chance {
-            individual.mutate(rng);
+        individual.check_brain()?;
+
+        Ok(individual)
+    }
+
+    /// Brains saved before the bot's inputs or outputs changed can't make sense of the game, and
+    /// corrupt or hand edited ones can't run at all.
+    pub fn check_brain(&self) -> eyre::Result<()> {
+        self.brain.check_shape()?;
+
+        let input_count = self.brain.input_count();
+        let output_count = self.brain.output_count();
+
+        if input_count != INPUT_COUNT || output_count != OUTPUT_COUNT {
+            eyre::bail!(
+                "the bot was saved by an older version of the game, its brain has {input_count} \
+                 inputs and {output_count} outputs instead of {INPUT_COUNT} and {OUTPUT_COUNT}"
+            );
         }
 
-        individual
+        Ok(())
+    }
+
+    pub fn weight_count(&self) -> usize {
+        self.brain.weight_count()
     }
 
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, ron::ser::to_string_pretty(self, Default::default())?)?;
+
+        Ok(())
+    }
+
+    /// Keeps track of how well the individual is aiming. The rest of its [`PlayStats`] are filled
+    /// in by the game. Frames without a bullet in flight are fine.
     pub fn update(&mut self, game_info: GameInfo) {
-        let mut bullet_distance_to_target = (game_info
-            .bullet_position
-            .expect("must have bullet position")
-            - game_info.target_position)
-            .length()
-            .abs();
+        let Some(bullet_position) = game_info.bullet_position else {
+            return;
+        };
 
-        if bullet_distance_to_target <= game_info.target_size {
-            bullet_distance_to_target = game_info.target_size
-        }
+        let bullet_distance_to_target = (bullet_position - game_info.target_position)
+            .length()
+            .max(game_info.target_size);
 
-        let score = game_info.target_size / bullet_distance_to_target; // 0..1 or -1..1
-        if score > self.score {
-            self.score = score;
+        let aim = game_info.target_size / bullet_distance_to_target; // 0..1
+        if aim > self.stats.best_aim {
+            self.stats.best_aim = aim;
         }
     }
 
-    pub fn play(&self, game_info: &GameInfo) -> [Command; 2] {
-        let position = game_info.position.normalize();
+    /// Decides what to do this frame: which way to rotate, then whether to fire, thrust and
+    /// teleport.
+    pub fn play(&self, game_info: &GameInfo) -> [Command; 4] {
+        let half_arena_size = game_info.arena_size / 2.;
+        let position = game_info.position / half_arena_size;
         let rotation = game_info.aim_rotation / PI;
-        let target_position = game_info.target_position.normalize();
-        let target_velocity = game_info.target_velocity.normalize();
-
-        let inputs = [
-            position.x,
-            position.y,
-            rotation,
-            target_position.x,
-            target_position.y,
-            target_velocity.x,
-            target_velocity.y,
-        ];
-        let rotation_guess = match self.rotate_perceptron.guess(&inputs) {
-            1 => Command::RotateRight,
-            -1 => Command::RotateLeft,
-            0 => Command::Nothing,
-            _ => unreachable!(),
-        };
-        let fire_guess = match self.fire_perceptron.guess(&inputs) {
-            1 => Command::Fire,
+
+        let mut inputs = vec![position.x, position.y, rotation];
+        for hazard in &game_info.hazards {
+            let offset = hazard.offset / half_arena_size;
+            let velocity = hazard.velocity / game_info.bullet_speed;
+            let size = hazard.size / half_arena_size.min_element();
+
+            inputs.extend([offset.x, offset.y, velocity.x, velocity.y, size]);
+            inputs.extend(
+                [HazardKind::Asteroid, HazardKind::Ufo, HazardKind::UfoBullet].map(|kind| {
+                    if hazard.kind == kind {
+                        1.
+                    } else {
+                        0.
+                    }
+                }),
+            );
+        }
+
+        let outputs = self.brain.output(&inputs);
+
+        let rotation_guess = match outputs[0] {
+            output if output > 0.5 => Command::RotateRight,
+            output if output < -0.5 => Command::RotateLeft,
             _ => Command::Nothing,
         };
+        let fire_guess = Self::guess(outputs[1], Command::Fire);
+        let thrust_guess = Self::guess(outputs[2], Command::Thrust);
+        let teleport_guess = Self::guess(outputs[3], Command::Teleport);
 
-        [rotation_guess, fire_guess]
+        [rotation_guess, fire_guess, thrust_guess, teleport_guess]
     }
 
-    fn mutate(&mut self, rng: &mut ThreadRng) {
-        if rng.gen_bool(0.5) {
-            self.rotate_perceptron.mutate(rng);
+    fn guess(output: f32, command: Command) -> Command {
+        if output > 0.5 {
+            command
         } else {
-            self.fire_perceptron.mutate(rng);
+            Command::Nothing
         }
     }
+
+    pub fn mutate(&mut self, mutation: Mutation, rng: &mut impl Rng) {
+        self.brain.mutate(mutation, rng);
+    }
+
+    /// All of the brain's weights, one after the other.
+    pub fn weights(&self) -> Vec<f32> {
+        self.brain.weights()
+    }
 }
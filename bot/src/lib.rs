@@ -1,48 +1,118 @@
 pub mod command;
+pub mod config;
+pub mod crossover;
+pub mod fitness;
 pub mod game_info;
 pub mod individual;
 mod logic;
-pub mod perceptron;
+pub mod metrics;
+pub mod network;
 
 use command::Command;
+use config::BotConfig;
 use game_info::GameInfo;
+use individual::Individual;
+pub use logic::SelectionStrategy;
 use logic::{create_population, generation, Population};
+use metrics::{GenerationStats, MetricsLog};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Instant};
 
+#[derive(Serialize, Deserialize)]
 pub struct Bot {
     pub population: Population,
-    graded_retain_percent: f32,
-    nongraded_retain_percent: f32,
+    /// Older checkpoints without a config load with `BotConfig::default()`.
+    #[serde(default)]
+    pub config: BotConfig,
     pub generation_count: u64,
-    mutation_chance: f32,
-    pub population_size: u8,
+    rng: ChaCha8Rng,
+    #[serde(skip)]
+    metrics_log: Option<MetricsLog>,
+    #[serde(skip, default = "Instant::now")]
+    generation_start: Instant,
 }
 
 impl Bot {
-    pub fn new(population_size: u8) -> Self {
-        let population = create_population(population_size);
-        let graded_retain_percent = 0.3;
-        let nongraded_retain_percent = 0.2;
+    pub fn new(population_size: usize) -> eyre::Result<Self> {
+        Self::from_seed(population_size, thread_rng().gen())
+    }
+
+    /// Creates the same population, and evolves it the same way, every time for a given seed.
+    pub fn from_seed(population_size: usize, seed: u64) -> eyre::Result<Self> {
+        BotConfig::new()
+            .population_size(population_size)
+            .build(seed)
+    }
+
+    pub fn from_config(config: BotConfig, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let population = create_population(config.population_size, &mut rng);
         let generation_count = 1;
-        let mutation_chance = 0.25;
 
         Self {
             population,
-            graded_retain_percent,
-            nongraded_retain_percent,
+            config,
             generation_count,
-            mutation_chance,
-            population_size,
+            rng,
+            metrics_log: None,
+            generation_start: Instant::now(),
+        }
+    }
+
+    /// Loads a population saved with [`Bot::save`], along with its settings and random state so
+    /// that evolution carries on exactly where it left off.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let bot: Self = ron::from_str(&std::fs::read_to_string(path)?)?;
+
+        bot.config.validate()?;
+
+        for individual in &bot.population {
+            individual.check_brain()?;
         }
+
+        // crossover lines the parents' weights up one by one
+        let weight_counts = bot.population.iter().map(Individual::weight_count);
+        if weight_counts.clone().min() != weight_counts.max() {
+            eyre::bail!("the individuals' brains aren't all the same shape");
+        }
+
+        Ok(bot)
     }
 
-    pub fn play(&self, game_info: GameInfo) -> Vec<[Command; 2]> {
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, ron::ser::to_string_pretty(self, Default::default())?)?;
+
+        Ok(())
+    }
+
+    pub fn play(&self, game_info: GameInfo) -> Vec<[Command; 4]> {
         self.population
-            .iter()
+            .par_iter()
             .map(move |individual| individual.play(&game_info))
-            .collect::<Vec<[Command; 2]>>()
+            .collect::<Vec<[Command; 4]>>()
+    }
+
+    /// Scores every individual with `fitness`, spread across all CPU cores. For training to be
+    /// reproducible, `fitness` should only depend on the individual it's given, for example by
+    /// playing games with their own seeded RNGs rather than `thread_rng`.
+    pub fn evaluate(&mut self, fitness: impl Fn(&Individual) -> f32 + Sync) {
+        self.population
+            .par_iter_mut()
+            .for_each(|individual| individual.score = fitness(individual));
     }
 
-    pub fn run(&mut self) {
+    /// Records the stats of every generation from now on.
+    pub fn log_metrics(&mut self, metrics_log: MetricsLog) {
+        self.metrics_log = Some(metrics_log);
+    }
+
+    /// Breeds the next generation from the scored population, and returns how the scored one did.
+    /// The next generation is bred even when its stats can't be recorded, the error is returned
+    /// once it has been.
+    pub fn run(&mut self) -> eyre::Result<GenerationStats> {
         // let successful: Vec<&individual::Individual> = self
         //     .population
         //     .iter()
@@ -58,13 +128,22 @@ impl Bot {
         // }
 
         let population = self.population.clone();
-        self.population = generation(
-            population,
-            self.graded_retain_percent,
-            self.nongraded_retain_percent,
-            self.mutation_chance,
+        let (next_population, mutation_count) = generation(population, &self.config, &mut self.rng);
+        let stats = GenerationStats::new(
+            self.generation_count,
+            &self.population,
+            mutation_count,
+            self.generation_start.elapsed(),
         );
 
+        self.population = next_population;
         self.generation_count += 1;
+        self.generation_start = Instant::now();
+
+        if let Some(metrics_log) = &self.metrics_log {
+            metrics_log.record(&stats)?;
+        }
+
+        Ok(stats)
     }
 }
@@ -1,60 +1,388 @@
 use super::individual::Individual;
-use rand::{seq::SliceRandom, thread_rng};
+use crate::config::BotConfig;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::{index, SliceRandom},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
 
 pub type Population = Vec<Individual>;
 
-pub fn create_population(population_size: u8) -> Population {
+/// How the individuals that parent the next generation are picked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SelectionStrategy {
+    /// The best `graded_retain_percent`, plus a random `nongraded_retain_percent` of the rest.
+    #[default]
+    Truncation,
+    /// Each survivor is the best of `size` individuals picked at random.
+    Tournament { size: usize },
+    /// Individuals survive with a chance proportional to their score.
+    Roulette,
+    /// Individuals survive with a chance proportional to their rank, so one runaway score
+    /// doesn't crowd out everyone else.
+    Rank,
+    /// The best `elite_count` always survive, and `then` picks the rest.
+    Elitism {
+        elite_count: usize,
+        then: Box<SelectionStrategy>,
+    },
+}
+
+impl SelectionStrategy {
+    /// Picks the survivors from a population sorted best first.
+    fn pick(
+        &self,
+        mut population: Population,
+        graded_count: usize,
+        nongraded_count: usize,
+        rng: &mut impl Rng,
+    ) -> Population {
+        let survivor_count = (graded_count + nongraded_count).min(population.len());
+
+        match self {
+            SelectionStrategy::Truncation => {
+                let graded_count = graded_count.min(population.len());
+                let nongraded_selected = population[graded_count..]
+                    .choose_multiple(rng, nongraded_count)
+                    .map(ToOwned::to_owned)
+                    .collect();
+
+                population.truncate(graded_count);
+
+                [population, nongraded_selected].concat()
+            }
+            SelectionStrategy::Tournament { size } => (0..survivor_count)
+                .map(|_| {
+                    let entrants =
+                        index::sample(rng, population.len(), (*size).max(1).min(population.len()));
+                    // the population is sorted, so the lowest index is the fittest entrant
+                    let winner = entrants.into_iter().min().unwrap();
+
+                    population.remove(winner)
+                })
+                .collect(),
+            SelectionStrategy::Roulette => {
+                let worst_score = population.last().map_or(0.0, |worst| worst.score);
+
+                pick_weighted(population, survivor_count, rng, |_, individual| {
+                    individual.score - worst_score
+                })
+            }
+            SelectionStrategy::Rank => {
+                pick_weighted(population, survivor_count, rng, |rank, _| rank as f32)
+            }
+            SelectionStrategy::Elitism { elite_count, then } => {
+                let elite_count = (*elite_count).min(survivor_count);
+                let rest = population.split_off(elite_count);
+                // the elites take up graded places first, then nongraded ones
+                let nongraded_count =
+                    nongraded_count.saturating_sub(elite_count.saturating_sub(graded_count));
+                let graded_count = graded_count.saturating_sub(elite_count);
+                let picked = then.pick(rest, graded_count, nongraded_count, rng);
+
+                [population, picked].concat()
+            }
+        }
+    }
+}
+
+/// Picks `count` individuals without replacement. `weight` is given how many individuals are
+/// worse than the one being weighed, and the individual itself.
+fn pick_weighted(
+    mut population: Population,
+    count: usize,
+    rng: &mut impl Rng,
+    weight: impl Fn(usize, &Individual) -> f32,
+) -> Population {
+    let mut picked = vec![];
+
+    for _ in 0..count {
+        let length = population.len();
+        let weights = population
+            .iter()
+            .enumerate()
+            .map(|(index, individual)| weight(length - index - 1, individual));
+        // when every weight is zero there's nothing to prefer, so pick anyone
+        let index = match WeightedIndex::new(weights) {
+            Ok(distribution) => distribution.sample(rng),
+            Err(_) => rng.gen_range(0..length),
+        };
+
+        picked.push(population.remove(index));
+    }
+
+    picked
+}
+
+pub fn create_population(population_size: usize, rng: &mut impl Rng) -> Population {
     let mut population = vec![];
-    let mut rng = thread_rng();
 
     for _ in 0..population_size {
-        population.push(Individual::new(&mut rng));
+        population.push(Individual::new(rng));
     }
     population
 }
 
 pub fn selection(
     mut population: Population,
+    strategy: &SelectionStrategy,
     graded_retain_percent: f32,
     nongraded_retain_percent: f32,
+    rng: &mut impl Rng,
 ) -> Population {
-    population.sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    population.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
 
     let keep_count = (population.len() as f32 * graded_retain_percent) as usize;
     let keep_ungraded_count = (population.len() as f32 * nongraded_retain_percent) as usize;
-    let selected = population[0..keep_count].to_vec();
-    let mut rng = thread_rng();
-
-    let nongraded_selected = population[keep_count..]
-        .choose_multiple(&mut rng, keep_ungraded_count)
-        .map(ToOwned::to_owned)
-        .collect();
+    // small populations round down to nobody, but the next generation needs a parent
+    let keep_count = if keep_count + keep_ungraded_count == 0 {
+        1
+    } else {
+        keep_count
+    };
 
-    let selected = [selected, nongraded_selected].concat();
-
-    selected
+    strategy.pick(population, keep_count, keep_ungraded_count, rng)
 }
 
+/// Breeds the next generation, returning it along with how many children mutated.
 pub fn generation(
     population: Population,
-    graded_retain_percent: f32,
-    nongraded_retain_percent: f32,
-    mutation_chance: f32,
-) -> Population {
+    config: &BotConfig,
+    rng: &mut impl Rng,
+) -> (Population, usize) {
     let population_count = population.len();
-    let mut survivors = selection(population, graded_retain_percent, nongraded_retain_percent);
+    let mut survivors = selection(
+        population,
+        &config.selection_strategy,
+        config.graded_retain_percent,
+        config.nongraded_retain_percent,
+        rng,
+    );
     let mut children = vec![];
-    let mut rng = thread_rng();
+    let mut mutation_count = 0;
 
     while (children.len() + survivors.len()) < population_count {
-        let parent_1 = survivors.choose(&mut rng).unwrap();
-        let parent_2 = survivors.choose(&mut rng).unwrap();
-        let child = Individual::new_from_parents(parent_1, parent_2, mutation_chance, &mut rng);
+        let parent_1 = survivors.choose(rng).unwrap();
+        let parent_2 = survivors.choose(rng).unwrap();
+        let mut child = Individual::new_from_parents(parent_1, parent_2, &config.crossover, rng);
+
+        if rng.gen_range(0.0..=1.0) < config.mutation_chance {
+            child.mutate(config.mutation, rng);
+            mutation_count += 1;
+        }
 
         children.push(child);
     }
 
     survivors.extend(children);
 
-    survivors
+    (survivors, mutation_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    /// Individuals scored from `count - 1` down to 0, so their score gives away their rank.
+    fn scored_population(count: usize, rng: &mut impl Rng) -> Population {
+        (0..count)
+            .map(|rank| {
+                let mut individual = Individual::new(rng);
+                individual.score = (count - rank - 1) as f32;
+                individual
+            })
+            .collect()
+    }
+
+    #[test]
+    fn small_populations_keep_a_survivor() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let config = BotConfig::default();
+
+        for count in 2..=3 {
+            let population = scored_population(count, &mut rng);
+            let survivors = selection(
+                population,
+                &SelectionStrategy::Truncation,
+                0.3,
+                0.2,
+                &mut rng,
+            );
+
+            assert_eq!(survivors.len(), 1);
+            assert_eq!(survivors[0].score, (count - 1) as f32);
+
+            let (next_population, _) =
+                generation(scored_population(count, &mut rng), &config, &mut rng);
+
+            assert_eq!(next_population.len(), count);
+        }
+    }
+
+    fn scores(population: &Population) -> Vec<f32> {
+        population
+            .iter()
+            .map(|individual| individual.score)
+            .collect()
+    }
+
+    fn no_one_picked_twice(population: &Population) -> bool {
+        let mut scores = scores(population);
+        scores.sort_unstable_by(f32::total_cmp);
+        scores.dedup();
+
+        scores.len() == population.len()
+    }
+
+    #[test]
+    fn every_strategy_picks_as_many_survivors_as_asked() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let strategies = [
+            SelectionStrategy::Truncation,
+            SelectionStrategy::Tournament { size: 3 },
+            SelectionStrategy::Roulette,
+            SelectionStrategy::Rank,
+            SelectionStrategy::Elitism {
+                elite_count: 2,
+                then: Box::new(SelectionStrategy::Roulette),
+            },
+        ];
+
+        for strategy in strategies {
+            for _ in 0..20 {
+                let survivors = strategy.pick(scored_population(10, &mut rng), 3, 2, &mut rng);
+
+                assert_eq!(survivors.len(), 5, "{strategy:?}");
+                assert!(no_one_picked_twice(&survivors), "{strategy:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn truncation_keeps_the_best_then_random_others() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let survivors =
+            SelectionStrategy::Truncation.pick(scored_population(10, &mut rng), 3, 2, &mut rng);
+
+        assert_eq!(scores(&survivors)[..3], [9.0, 8.0, 7.0]);
+        assert!(scores(&survivors)[3..].iter().all(|&score| score < 7.0));
+    }
+
+    #[test]
+    fn tournaments_of_everyone_are_won_by_the_best() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let survivors = SelectionStrategy::Tournament { size: 10 }.pick(
+            scored_population(10, &mut rng),
+            3,
+            1,
+            &mut rng,
+        );
+
+        assert_eq!(scores(&survivors), [9.0, 8.0, 7.0, 6.0]);
+    }
+
+    #[test]
+    fn roulette_and_rank_never_pick_the_worst() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        for strategy in [SelectionStrategy::Roulette, SelectionStrategy::Rank] {
+            for _ in 0..20 {
+                let survivors = strategy.pick(scored_population(10, &mut rng), 6, 2, &mut rng);
+
+                // the worst weighs nothing, and there's always someone better left to pick
+                assert!(!scores(&survivors).contains(&0.0), "{strategy:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn elites_survive_before_the_rest_are_picked() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let strategy = SelectionStrategy::Elitism {
+            elite_count: 3,
+            then: Box::new(SelectionStrategy::Rank),
+        };
+
+        for _ in 0..20 {
+            let survivors = strategy.pick(scored_population(10, &mut rng), 2, 3, &mut rng);
+
+            assert_eq!(survivors.len(), 5);
+            assert_eq!(scores(&survivors)[..3], [9.0, 8.0, 7.0]);
+        }
+    }
+
+    #[test]
+    fn nested_elitism_keeps_the_elites_of_each_level() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let strategy = SelectionStrategy::Elitism {
+            elite_count: 2,
+            then: Box::new(SelectionStrategy::Elitism {
+                elite_count: 1,
+                then: Box::new(SelectionStrategy::Roulette),
+            }),
+        };
+
+        for _ in 0..20 {
+            let survivors = strategy.pick(scored_population(10, &mut rng), 3, 2, &mut rng);
+
+            assert_eq!(survivors.len(), 5);
+            assert!(no_one_picked_twice(&survivors));
+            assert_eq!(scores(&survivors)[..3], [9.0, 8.0, 7.0]);
+        }
+    }
+
+    #[test]
+    fn elitism_never_keeps_more_elites_than_survivors() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let strategy = SelectionStrategy::Elitism {
+            elite_count: 8,
+            then: Box::new(SelectionStrategy::Truncation),
+        };
+        let survivors = strategy.pick(scored_population(10, &mut rng), 2, 1, &mut rng);
+
+        assert_eq!(scores(&survivors), [9.0, 8.0, 7.0]);
+    }
+
+    #[test]
+    fn selection_sorts_scores_that_arent_numbers() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut population = scored_population(4, &mut rng);
+        population[2].score = f32::NAN;
+
+        let survivors = selection(
+            population,
+            &SelectionStrategy::Truncation,
+            0.5,
+            0.0,
+            &mut rng,
+        );
+
+        assert_eq!(survivors.len(), 2);
+    }
+
+    #[test]
+    fn weighted_picks_without_any_weight_pick_anyone() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let survivors = pick_weighted(scored_population(5, &mut rng), 5, &mut rng, |_, _| 0.0);
+
+        assert_eq!(survivors.len(), 5);
+        assert!(no_one_picked_twice(&survivors));
+    }
+
+    #[test]
+    fn roulette_with_equal_scores_still_picks() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut population = scored_population(6, &mut rng);
+        for individual in &mut population {
+            individual.score = 1.0;
+        }
+
+        let survivors = SelectionStrategy::Roulette.pick(population, 2, 1, &mut rng);
+
+        assert_eq!(survivors.len(), 3);
+    }
 }
@@ -0,0 +1,247 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::logic::Population;
+
+const CSV_HEADER: &str =
+    "generation,best_fitness,mean_fitness,median_fitness,weight_diversity,mutation_count,wall_time";
+
+/// How one generation did, recorded by [`crate::Bot::run`].
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+    pub median_fitness: f32,
+    /// The standard deviation of each weight across the population, averaged over all weights.
+    /// It drops towards zero as the population converges.
+    pub weight_diversity: f32,
+    /// How many children mutated while breeding the next generation.
+    pub mutation_count: usize,
+    /// Seconds spent on this generation, playing and breeding.
+    pub wall_time: f64,
+}
+
+impl GenerationStats {
+    pub fn new(
+        generation: u64,
+        population: &Population,
+        mutation_count: usize,
+        wall_time: Duration,
+    ) -> Self {
+        let mut scores: Vec<f32> = population
+            .iter()
+            .map(|individual| individual.score)
+            .collect();
+        scores.sort_by(f32::total_cmp);
+
+        let best_fitness = scores.last().copied().unwrap_or_default();
+        let mean_fitness = scores.iter().sum::<f32>() / scores.len().max(1) as f32;
+        let median_fitness = median(&scores);
+
+        Self {
+            generation,
+            best_fitness,
+            mean_fitness,
+            median_fitness,
+            weight_diversity: weight_diversity(population),
+            mutation_count,
+            wall_time: wall_time.as_secs_f64(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.generation,
+            self.best_fitness,
+            self.mean_fitness,
+            self.median_fitness,
+            self.weight_diversity,
+            self.mutation_count,
+            self.wall_time
+        )
+    }
+}
+
+/// `scores` must be sorted.
+fn median(scores: &[f32]) -> f32 {
+    match scores.len() {
+        0 => 0.0,
+        length if length % 2 == 0 => (scores[length / 2 - 1] + scores[length / 2]) / 2.0,
+        length => scores[length / 2],
+    }
+}
+
+/// Brains bred together always have the same shape, but in case they don't only the weights
+/// every brain has are compared.
+fn weight_diversity(population: &Population) -> f32 {
+    let weights: Vec<Vec<f32>> = population
+        .iter()
+        .map(|individual| individual.weights())
+        .collect();
+    let weight_count = weights.iter().map(Vec::len).min().unwrap_or_default();
+    if weight_count == 0 {
+        return 0.0;
+    }
+
+    let count = weights.len() as f32;
+    let total_deviation: f32 = (0..weight_count)
+        .map(|index| {
+            let mean = weights.iter().map(|weights| weights[index]).sum::<f32>() / count;
+            let variance = weights
+                .iter()
+                .map(|weights| (weights[index] - mean).powi(2))
+                .sum::<f32>()
+                / count;
+
+            variance.sqrt()
+        })
+        .sum();
+
+    total_deviation / weight_count as f32
+}
+
+/// Appends each generation's stats to a CSV file, and optionally a JSON lines file too, so a
+/// resumed run carries on the same chart.
+#[derive(Debug, Clone)]
+pub struct MetricsLog {
+    csv_path: PathBuf,
+    json_lines_path: Option<PathBuf>,
+}
+
+impl MetricsLog {
+    pub fn new(csv_path: PathBuf, json_lines_path: Option<PathBuf>) -> Self {
+        Self {
+            csv_path,
+            json_lines_path,
+        }
+    }
+
+    pub fn record(&self, stats: &GenerationStats) -> eyre::Result<()> {
+        let needs_header = std::fs::metadata(&self.csv_path).map_or(true, |file| file.len() == 0);
+        let mut csv = append(&self.csv_path)?;
+
+        if needs_header {
+            writeln!(csv, "{CSV_HEADER}")?;
+        }
+        writeln!(csv, "{}", stats.to_csv())?;
+
+        if let Some(path) = &self.json_lines_path {
+            writeln!(append(path)?, "{}", serde_json::to_string(stats)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn append(path: &Path) -> std::io::Result<std::fs::File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::{individual::Individual, network::Activation};
+
+    fn stats() -> GenerationStats {
+        GenerationStats {
+            generation: 3,
+            best_fitness: 2.5,
+            mean_fitness: 1.25,
+            median_fitness: 1.0,
+            weight_diversity: 0.5,
+            mutation_count: 4,
+            wall_time: 1.5,
+        }
+    }
+
+    #[test]
+    fn median_of_odd_even_and_empty_scores() {
+        assert_eq!(median(&[]), 0.0);
+        assert_eq!(median(&[1.0, 2.0, 7.0]), 2.0);
+        assert_eq!(median(&[1.0, 2.0, 4.0, 7.0]), 3.0);
+    }
+
+    #[test]
+    fn clones_have_no_diversity() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let individual = Individual::new(&mut rng);
+
+        assert_eq!(weight_diversity(&vec![individual.clone(), individual]), 0.0);
+        assert_eq!(weight_diversity(&vec![]), 0.0);
+    }
+
+    #[test]
+    fn diversity_is_the_mean_deviation_of_each_weight() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let population = vec![Individual::new(&mut rng), Individual::new(&mut rng)];
+        let (one, two) = (population[0].weights(), population[1].weights());
+        // with two individuals, each weight deviates from their mean by half their difference
+        let expected = one
+            .iter()
+            .zip(&two)
+            .map(|(one, two)| (one - two).abs() / 2.0)
+            .sum::<f32>()
+            / one.len() as f32;
+
+        assert!((weight_diversity(&population) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn diversity_of_mixed_shapes_compares_the_shared_weights() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let population = vec![
+            Individual::with_brain(&mut rng, &[8], Activation::Tanh),
+            Individual::with_brain(&mut rng, &[4], Activation::Tanh),
+        ];
+
+        assert!(weight_diversity(&population) > 0.0);
+    }
+
+    #[test]
+    fn csv_rows_follow_the_header() {
+        assert_eq!(stats().to_csv(), "3,2.5,1.25,1,0.5,4,1.5");
+        assert_eq!(
+            CSV_HEADER.split(',').count(),
+            stats().to_csv().split(',').count()
+        );
+    }
+
+    #[test]
+    fn records_append_rows_under_one_header() {
+        let directory = std::env::temp_dir().join(format!("metrics-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let csv_path = directory.join("metrics.csv");
+        let json_lines_path = directory.join("metrics.jsonl");
+        let log = MetricsLog::new(csv_path.clone(), Some(json_lines_path.clone()));
+
+        log.record(&stats()).unwrap();
+        log.record(&stats()).unwrap();
+
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let json_lines = std::fs::read_to_string(&json_lines_path).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let row = stats().to_csv();
+        assert_eq!(csv.lines().collect::<Vec<_>>(), [CSV_HEADER, &row, &row]);
+
+        let lines: Vec<serde_json::Value> = json_lines
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["generation"], 3);
+        assert_eq!(lines[0]["mutation_count"], 4);
+        assert_eq!(lines[0]["best_fitness"], 2.5);
+    }
+}
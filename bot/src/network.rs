@@ -0,0 +1,337 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+use crate::crossover::Crossover;
+
+/// How a network's weights change when it mutates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mutation {
+    /// Chance that any one weight is nudged.
+    pub rate: f64,
+    /// Standard deviation of the gaussian noise added to a nudged weight.
+    pub strength: f32,
+}
+
+impl Default for Mutation {
+    fn default() -> Self {
+        Self {
+            rate: 0.1,
+            strength: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    Tanh,
+    Relu,
+    Sigmoid,
+}
+
+impl Activation {
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.0),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+        }
+    }
+}
+
+/// A fully connected layer. Each output has a row of `input_count` weights followed by a bias.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layer {
+    pub weights: Vec<f32>,
+    pub input_count: usize,
+    pub output_count: usize,
+    pub activation: Activation,
+}
+
+impl Layer {
+    pub fn new(
+        rng: &mut impl Rng,
+        input_count: usize,
+        output_count: usize,
+        activation: Activation,
+    ) -> Self {
+        let weights = (0..(input_count + 1) * output_count)
+            .map(|_| rng.gen_range(-1.0..=1.0))
+            .collect();
+
+        Self {
+            weights,
+            input_count,
+            output_count,
+            activation,
+        }
+    }
+
+    pub fn output(&self, inputs: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks(self.input_count + 1)
+            .map(|row| {
+                let (weights, bias) = row.split_at(self.input_count);
+                let sum: f32 = weights
+                    .iter()
+                    .zip(inputs)
+                    .map(|(weight, input)| weight * input)
+                    .sum();
+
+                self.activation.apply(sum + bias[0])
+            })
+            .collect()
+    }
+}
+
+/// A feed-forward network: the inputs run through each hidden layer in turn and then the output
+/// layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Network {
+    pub layers: Vec<Layer>,
+}
+
+impl Network {
+    /// `layer_sizes` runs from the input count, through the size of each hidden layer, to the
+    /// output count. Hidden layers use `hidden_activation` and the last layer uses
+    /// `output_activation`.
+    pub fn new(
+        rng: &mut impl Rng,
+        layer_sizes: &[usize],
+        hidden_activation: Activation,
+        output_activation: Activation,
+    ) -> Self {
+        assert!(
+            layer_sizes.len() >= 2,
+            "a network needs at least an input and an output size"
+        );
+
+        let last = layer_sizes.len() - 2;
+        let layers = layer_sizes
+            .windows(2)
+            .enumerate()
+            .map(|(index, sizes)| {
+                let activation = if index == last {
+                    output_activation
+                } else {
+                    hidden_activation
+                };
+
+                Layer::new(rng, sizes[0], sizes[1], activation)
+            })
+            .collect();
+
+        Self { layers }
+    }
+
+    /// Both parents must have the same shape, which the child shares.
+    pub fn new_from_parents(
+        parent_one: &Self,
+        parent_two: &Self,
+        crossover: &impl Crossover,
+        scores: [f32; 2],
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut child = parent_one.clone();
+        let weights =
+            crossover.crossover(&parent_one.weights(), &parent_two.weights(), scores, rng);
+
+        for (weight, child_weight) in child.weights_mut().zip(weights) {
+            *weight = child_weight;
+        }
+
+        child
+    }
+
+    pub fn output(&self, inputs: &[f32]) -> Vec<f32> {
+        self.layers
+            .iter()
+            .fold(inputs.to_vec(), |inputs, layer| layer.output(&inputs))
+    }
+
+    pub fn mutate(&mut self, mutation: Mutation, rng: &mut impl Rng) {
+        let noise = Normal::new(0.0, mutation.strength).expect("mutation strength must be finite");
+        let mut mutated = false;
+
+        for weight in self.weights_mut() {
+            if rng.gen_bool(mutation.rate) {
+                *weight += noise.sample(rng);
+                mutated = true;
+            }
+        }
+
+        // always change something so a mutation is never wasted
+        if !mutated {
+            let index = rng.gen_range(0..self.weight_count());
+            let weight = self.weights_mut().nth(index).unwrap();
+
+            *weight += noise.sample(rng);
+        }
+    }
+
+    /// Networks that weren't made by [`Network::new`], like ones edited by hand, can have a layer
+    /// that is missing weights or that doesn't take what the layer before it gives.
+    pub fn check_shape(&self) -> eyre::Result<()> {
+        for (index, layer) in self.layers.iter().enumerate() {
+            let weight_count = (layer.input_count + 1) * layer.output_count;
+
+            if layer.weights.len() != weight_count {
+                eyre::bail!(
+                    "layer {index} has {} weights instead of {weight_count}",
+                    layer.weights.len()
+                );
+            }
+        }
+
+        for (index, layers) in self.layers.windows(2).enumerate() {
+            if layers[0].output_count != layers[1].input_count {
+                eyre::bail!(
+                    "layer {index} has {} outputs but layer {} takes {} inputs",
+                    layers[0].output_count,
+                    index + 1,
+                    layers[1].input_count
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.layers.first().map_or(0, |layer| layer.input_count)
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.layers.last().map_or(0, |layer| layer.output_count)
+    }
+
+    pub fn weight_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.weights.len()).sum()
+    }
+
+    /// Every layer's weights, one after the other.
+    pub fn weights(&self) -> Vec<f32> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.weights.iter().copied())
+            .collect()
+    }
+
+    fn weights_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+        self.layers.iter_mut().flat_map(|layer| &mut layer.weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn changed_weights(before: &Network, after: &Network) -> usize {
+        before
+            .weights()
+            .iter()
+            .zip(after.weights())
+            .filter(|(before, after)| **before != *after)
+            .count()
+    }
+
+    #[test]
+    fn activations() {
+        assert_eq!(Activation::Tanh.apply(0.0), 0.0);
+        assert!(Activation::Tanh.apply(10.0) <= 1.0);
+        assert!(Activation::Tanh.apply(-10.0) >= -1.0);
+        assert_eq!(Activation::Relu.apply(-2.0), 0.0);
+        assert_eq!(Activation::Relu.apply(2.0), 2.0);
+        assert_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+        assert!((0.0..=1.0).contains(&Activation::Sigmoid.apply(-10.0)));
+        assert!((0.0..=1.0).contains(&Activation::Sigmoid.apply(10.0)));
+    }
+
+    #[test]
+    fn layer_adds_a_bias_to_each_weighted_sum() {
+        let layer = Layer {
+            weights: vec![1.0, 2.0, 0.5, -1.0, 0.0, 0.0],
+            input_count: 2,
+            output_count: 2,
+            activation: Activation::Relu,
+        };
+
+        assert_eq!(layer.output(&[1.0, 1.0]), vec![3.5, 0.0]);
+    }
+
+    #[test]
+    fn forward_pass_has_one_value_per_output() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let network = Network::new(
+            &mut rng,
+            &[5, 8, 3, 2],
+            Activation::Relu,
+            Activation::Sigmoid,
+        );
+
+        assert_eq!(network.layers.len(), 3);
+        assert_eq!(network.input_count(), 5);
+        assert_eq!(network.output_count(), 2);
+        assert_eq!(network.weight_count(), 6 * 8 + 9 * 3 + 4 * 2);
+
+        let outputs = network.output(&[0.1, -0.2, 0.3, -0.4, 0.5]);
+
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs.iter().all(|output| (0.0..=1.0).contains(output)));
+    }
+
+    #[test]
+    fn new_networks_have_the_right_shape() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let network = Network::new(&mut rng, &[5, 8, 2], Activation::Relu, Activation::Tanh);
+
+        assert!(network.check_shape().is_ok());
+    }
+
+    #[test]
+    fn layers_missing_weights_are_caught() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut network = Network::new(&mut rng, &[5, 8, 2], Activation::Relu, Activation::Tanh);
+
+        network.layers[1].weights.pop();
+
+        assert!(network.check_shape().is_err());
+    }
+
+    #[test]
+    fn layers_that_dont_chain_are_caught() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut network = Network::new(&mut rng, &[5, 8, 2], Activation::Relu, Activation::Tanh);
+
+        network.layers[1] = Layer::new(&mut rng, 7, 2, Activation::Tanh);
+
+        assert!(network.check_shape().is_err());
+    }
+
+    #[test]
+    fn mutation_rate_is_the_chance_each_weight_changes() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let network = Network::new(&mut rng, &[99, 10], Activation::Tanh, Activation::Tanh);
+        let mutate = |rate, rng: &mut ChaCha8Rng| {
+            let mut mutated = network.clone();
+            mutated.mutate(
+                Mutation {
+                    rate,
+                    strength: 0.5,
+                },
+                rng,
+            );
+            changed_weights(&network, &mutated)
+        };
+
+        // even a rate of 0 changes one weight, so a mutation is never wasted
+        assert_eq!(mutate(0.0, &mut rng), 1);
+        assert_eq!(mutate(1.0, &mut rng), network.weight_count());
+
+        let half = mutate(0.5, &mut rng);
+        assert!((400..600).contains(&half), "{half} of 1000 weights changed");
+    }
+}
@@ -0,0 +1,424 @@
+use std::{collections::BTreeMap, path::Path};
+
+use bevy::{
+    ecs::system::{Res, ResMut, Resource, SystemParam},
+    input::{
+        gamepad::{
+            Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads,
+        },
+        keyboard::KeyCode,
+        Axis, ButtonInput,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// Where an action does something, to tell which actions can share a key.
+const FLYING: u8 = 1;
+/// The title and game over screens.
+const TITLE: u8 = 1 << 1;
+/// The pause menu and the controls screen.
+const MENU: u8 = 1 << 2;
+
+/// Everything a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Fire,
+    Hyperspace,
+    /// Starts a game from the title or game over screen.
+    Start,
+    Pause,
+    BossKey,
+    /// Opens the controls screen from the title screen.
+    Controls,
+    /// Moves between the buttons of the pause menu and the controls screen.
+    MenuUp,
+    MenuDown,
+    /// Presses the button moved to with [`Action::MenuUp`] and [`Action::MenuDown`].
+    MenuSelect,
+    /// Flies the next individual while spectating.
+    NextIndividual,
+    ToggleWatching,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Thrust,
+        Action::Fire,
+        Action::Hyperspace,
+        Action::Start,
+        Action::Pause,
+        Action::BossKey,
+        Action::Controls,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuSelect,
+        Action::NextIndividual,
+        Action::ToggleWatching,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::RotateLeft => "Rotate left",
+            Action::RotateRight => "Rotate right",
+            Action::Thrust => "Thrust",
+            Action::Fire => "Fire",
+            Action::Hyperspace => "Hyperspace",
+            Action::Start => "Start",
+            Action::Pause => "Pause",
+            Action::BossKey => "Boss key",
+            Action::Controls => "Controls",
+            Action::MenuUp => "Menu up",
+            Action::MenuDown => "Menu down",
+            Action::MenuSelect => "Menu select",
+            Action::NextIndividual => "Watch next bot",
+            Action::ToggleWatching => "Toggle watching",
+        }
+    }
+
+    fn default_keys(&self) -> Vec<KeyCode> {
+        match self {
+            Action::RotateLeft => vec![KeyCode::ArrowLeft],
+            Action::RotateRight => vec![KeyCode::ArrowRight],
+            Action::Thrust => vec![KeyCode::ArrowUp],
+            Action::Fire => vec![KeyCode::Space],
+            Action::Hyperspace => vec![KeyCode::Enter],
+            Action::Start => vec![KeyCode::Space],
+            Action::Pause => vec![KeyCode::Escape, KeyCode::KeyP],
+            Action::BossKey => vec![KeyCode::KeyB],
+            Action::Controls => vec![KeyCode::KeyC],
+            Action::MenuUp => vec![KeyCode::ArrowUp],
+            Action::MenuDown => vec![KeyCode::ArrowDown],
+            Action::MenuSelect => vec![KeyCode::Enter],
+            Action::NextIndividual => vec![KeyCode::Tab],
+            Action::ToggleWatching => vec![KeyCode::KeyV],
+        }
+    }
+
+    fn screens(&self) -> u8 {
+        match self {
+            Action::RotateLeft
+            | Action::RotateRight
+            | Action::Thrust
+            | Action::Fire
+            | Action::Hyperspace
+            | Action::NextIndividual => FLYING,
+            Action::Start | Action::Controls => TITLE,
+            Action::MenuUp | Action::MenuDown | Action::MenuSelect => MENU,
+            Action::Pause => FLYING | MENU,
+            Action::BossKey | Action::ToggleWatching => FLYING | TITLE | MENU,
+        }
+    }
+
+    /// Whether a key can't do both, because the actions are used on the same screen. Actions
+    /// that never are can share keys, like Space for both Start and Fire.
+    fn clashes_with(&self, other: Action) -> bool {
+        *self != other && self.screens() & other.screens() != 0
+    }
+
+    /// The same buttons on every gamepad, these can't be rebound.
+    pub fn gamepad_buttons(&self) -> &'static [GamepadButtonType] {
+        match self {
+            Action::RotateLeft => &[GamepadButtonType::DPadLeft],
+            Action::RotateRight => &[GamepadButtonType::DPadRight],
+            Action::Thrust => &[GamepadButtonType::RightTrigger2, GamepadButtonType::South],
+            Action::Fire => &[GamepadButtonType::West],
+            Action::Hyperspace => &[GamepadButtonType::North],
+            Action::Start | Action::Pause => &[GamepadButtonType::Start],
+            Action::BossKey => &[GamepadButtonType::Select],
+            Action::MenuUp => &[GamepadButtonType::DPadUp],
+            Action::MenuDown => &[GamepadButtonType::DPadDown],
+            Action::MenuSelect => &[GamepadButtonType::South],
+            _ => &[],
+        }
+    }
+}
+
+/// Which keys do what, saved as `bindings.ron` in the config directory.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings(pub BTreeMap<Action, Vec<KeyCode>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        )
+    }
+}
+
+impl InputBindings {
+    /// Actions missing from the file keep their default keys, so a file saved before an action
+    /// was added still binds it.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let saved: Self = ron::from_str(&std::fs::read_to_string(path)?)?;
+        let mut bindings = Self::default();
+
+        bindings.0.extend(saved.0);
+
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        let bindings = ron::ser::to_string_pretty(self, Default::default())?;
+
+        std::fs::write(path, bindings)?;
+
+        Ok(())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn pressed(&self, action: Action, input: &ButtonInput<KeyCode>) -> bool {
+        input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, action: Action, input: &ButtonInput<KeyCode>) -> bool {
+        input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// Like [`ButtonInput::clear_just_pressed`], so no other system sees the same press.
+    pub fn clear_just_pressed(&self, action: Action, input: &mut ButtonInput<KeyCode>) -> bool {
+        let mut cleared = false;
+
+        // every key of the action is cleared, not just the first one that was pressed
+        for &key in self.keys(action) {
+            cleared |= input.clear_just_pressed(key);
+        }
+
+        cleared
+    }
+
+    /// Makes `key` the first key for `action` in place of the old first key, keeping the others.
+    /// An action that clashes with `action` and was using `key` gets the old first key instead.
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        let keys = self.keys(action);
+
+        if keys.contains(&key) {
+            return;
+        }
+
+        let old_key = keys.first().copied();
+
+        for (_, other_keys) in self
+            .0
+            .iter_mut()
+            .filter(|(&other, _)| action.clashes_with(other))
+        {
+            let Some(position) = other_keys.iter().position(|&other_key| other_key == key) else {
+                continue;
+            };
+
+            match old_key {
+                Some(old_key) if !other_keys.contains(&old_key) => other_keys[position] = old_key,
+                _ => {
+                    other_keys.remove(position);
+                }
+            }
+        }
+
+        let keys = self.0.entry(action).or_default();
+
+        match keys.first_mut() {
+            Some(first) => *first = key,
+            None => keys.push(key),
+        }
+    }
+
+    /// The keys for `action` the way the screens show them, like "Escape or P".
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.keys(action).iter().map(|&key| key_name(key)).collect();
+
+        if names.is_empty() {
+            "nothing".to_string()
+        } else {
+            names.join(" or ")
+        }
+    }
+}
+
+/// The keyboard through the [`InputBindings`] and every connected gamepad, so systems ask about
+/// actions rather than keys or buttons.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    keyboard: ResMut<'w, ButtonInput<KeyCode>>,
+    gamepad_buttons: ResMut<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    gamepads: Res<'w, Gamepads>,
+    bindings: Res<'w, InputBindings>,
+}
+
+impl ActionInput<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings.pressed(action, &self.keyboard)
+            || self
+                .buttons(action)
+                .any(|button| self.gamepad_buttons.pressed(button))
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.bindings.just_pressed(action, &self.keyboard)
+            || self
+                .buttons(action)
+                .any(|button| self.gamepad_buttons.just_pressed(button))
+    }
+
+    /// Like [`InputBindings::clear_just_pressed`], for the gamepads too.
+    pub fn clear_just_pressed(&mut self, action: Action) -> bool {
+        let mut cleared = self.bindings.clear_just_pressed(action, &mut self.keyboard);
+
+        let buttons: Vec<GamepadButton> = self.buttons(action).collect();
+
+        for button in buttons {
+            cleared |= self.gamepad_buttons.clear_just_pressed(button);
+        }
+
+        cleared
+    }
+
+    /// How hard to turn, from -1 for full right to 1 for full left. Keys and the d-pad turn all
+    /// the way, the left stick as far as it is pushed.
+    pub fn rotation(&self) -> f32 {
+        let digital = if self.pressed(Action::RotateLeft) {
+            1.
+        } else if self.pressed(Action::RotateRight) {
+            -1.
+        } else {
+            0.
+        };
+
+        self.gamepads
+            .iter()
+            .filter_map(|gamepad| {
+                self.gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            })
+            // pushing the stick right turns clockwise
+            .map(|stick| -stick)
+            .fold(digital, |strongest: f32, stick| {
+                if stick.abs() > strongest.abs() {
+                    stick
+                } else {
+                    strongest
+                }
+            })
+            .clamp(-1., 1.)
+    }
+
+    fn buttons(&self, action: Action) -> impl Iterator<Item = GamepadButton> + '_ {
+        self.gamepads.iter().flat_map(move |gamepad: Gamepad| {
+            action
+                .gamepad_buttons()
+                .iter()
+                .map(move |&button_type| GamepadButton::new(gamepad, button_type))
+        })
+    }
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_files_keep_the_default_keys_of_missing_actions() {
+        let path = std::env::temp_dir().join(format!("bindings-{}.ron", std::process::id()));
+        std::fs::write(&path, "({Fire: [KeyF], Pause: [KeyQ]})").unwrap();
+
+        let bindings = InputBindings::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let bindings = bindings.unwrap();
+
+        assert_eq!(bindings.keys(Action::Fire), [KeyCode::KeyF]);
+        assert_eq!(bindings.keys(Action::Pause), [KeyCode::KeyQ]);
+        assert_eq!(bindings.keys(Action::Thrust), [KeyCode::ArrowUp]);
+        assert_eq!(bindings.keys(Action::Start), [KeyCode::Space]);
+        assert_eq!(bindings.0.len(), Action::ALL.len());
+    }
+
+    #[test]
+    fn rebinding_keeps_the_other_keys() {
+        let mut bindings = InputBindings::default();
+
+        bindings.rebind(Action::Pause, KeyCode::KeyQ);
+
+        assert_eq!(bindings.keys(Action::Pause), [KeyCode::KeyQ, KeyCode::KeyP]);
+    }
+
+    #[test]
+    fn rebinding_to_a_key_the_action_has_changes_nothing() {
+        let mut bindings = InputBindings::default();
+
+        bindings.rebind(Action::Pause, KeyCode::KeyP);
+
+        assert_eq!(bindings, InputBindings::default());
+    }
+
+    #[test]
+    fn rebinding_swaps_keys_with_a_clashing_action() {
+        let mut bindings = InputBindings::default();
+
+        bindings.rebind(Action::Fire, KeyCode::Enter);
+
+        assert_eq!(bindings.keys(Action::Fire), [KeyCode::Enter]);
+        assert_eq!(bindings.keys(Action::Hyperspace), [KeyCode::Space]);
+    }
+
+    #[test]
+    fn rebinding_drops_a_clashing_key_that_would_be_doubled() {
+        let mut bindings = InputBindings::default();
+        // only a hand edited file can have clashing actions share a key
+        bindings.0.insert(Action::Fire, vec![KeyCode::KeyP]);
+
+        // Pause already has Fire's old key, so it would end up with P twice
+        bindings.rebind(Action::Fire, KeyCode::Escape);
+
+        assert_eq!(bindings.keys(Action::Fire), [KeyCode::Escape]);
+        assert_eq!(bindings.keys(Action::Pause), [KeyCode::KeyP]);
+    }
+
+    #[test]
+    fn the_default_keys_never_clash() {
+        let bindings = InputBindings::default();
+
+        for action in Action::ALL {
+            for other in Action::ALL
+                .into_iter()
+                .filter(|&other| action.clashes_with(other))
+            {
+                for key in bindings.keys(action) {
+                    assert!(
+                        !bindings.keys(other).contains(key),
+                        "{action:?} and {other:?} both use {key:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn title_actions_can_share_keys_with_flying() {
+        let mut bindings = InputBindings::default();
+
+        bindings.rebind(Action::Start, KeyCode::ArrowUp);
+
+        assert_eq!(bindings.keys(Action::Start), [KeyCode::ArrowUp]);
+        assert_eq!(bindings.keys(Action::Thrust), [KeyCode::ArrowUp]);
+    }
+}
@@ -1,22 +1,25 @@
+use std::{f32::consts::PI, ops::Range};
+
 use bevy::{
     ecs::component::Component,
-    math::{bounding::BoundingCircle, Quat, Vec3},
+    math::{Quat, Vec3},
     prelude::{Deref, DerefMut, Vec2},
     time::Timer,
 };
+use rand::Rng;
 
-use crate::resources::WorldSize;
+use crate::{bindings::Action, resources::WorldSize};
 
 #[derive(Component, Clone, Debug, Deref, DerefMut, Copy, Default)]
 pub struct Position(pub Vec3);
 
 impl Position {
-    pub fn new_random_edge(world_size: &WorldSize) -> Self {
-        Self(world_size.get_random_edge())
+    pub fn new_random_edge(world_size: &WorldSize, rng: &mut impl Rng) -> Self {
+        Self(world_size.get_random_edge(rng))
     }
 
-    pub fn set_random(&mut self, world_size: &WorldSize) {
-        self.0 = world_size.get_random_coords();
+    pub fn set_random(&mut self, world_size: &WorldSize, rng: &mut impl Rng) {
+        self.0 = world_size.get_random_coords(rng);
     }
 }
 
@@ -44,6 +47,16 @@ pub struct Thrust(pub bool);
 #[derive(Component, Deref, DerefMut)]
 pub struct Firing(pub bool);
 
+#[derive(Component, Deref, DerefMut)]
+pub struct Teleport(pub bool);
+
+/// Where an entity was at the start of the current fixed tick, so it can be drawn between ticks.
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct PreviousPosition(pub Vec3);
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct PreviousRotation(pub Quat);
+
 #[derive(Component)]
 pub struct MainCamera;
 
@@ -62,25 +75,70 @@ pub struct BulletTimer(pub Timer);
 #[derive(Component)]
 pub struct Asteroid;
 
-#[derive(Component, Deref, DerefMut)]
-pub struct CollisionDetection(pub BoundingCircle);
+/// Which generation an asteroid is. Large ones break into mediums, mediums into smalls, and
+/// smalls are gone for good.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
 
-#[derive(Component)]
-pub struct Collidable;
+impl AsteroidSize {
+    pub fn radius(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => 130.,
+            AsteroidSize::Medium => 65.,
+            AsteroidSize::Small => 32.5,
+        }
+    }
 
-#[derive(Component, Deref, DerefMut)]
-pub struct Size(pub f32);
+    pub fn speed_range(&self) -> Range<f32> {
+        match self {
+            AsteroidSize::Large => 14.5..15.5,
+            AsteroidSize::Medium => 29.0..31.0,
+            AsteroidSize::Small => 58.0..62.0,
+        }
+    }
+
+    /// What it breaks into when shot, and how many of them.
+    pub fn split(&self) -> Option<(AsteroidSize, usize)> {
+        match self {
+            AsteroidSize::Large => Some((AsteroidSize::Medium, 2)),
+            AsteroidSize::Medium => Some((AsteroidSize::Small, 2)),
+            AsteroidSize::Small => None,
+        }
+    }
 
-impl Size {
-    pub fn from_scale(scale: f32) -> Self {
-        Self(65. * scale)
+    /// The angle, in radians, that the pieces fan out over around the way it was heading.
+    pub fn split_spread(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => PI / 3.,
+            AsteroidSize::Medium => PI / 2.,
+            AsteroidSize::Small => 0.,
+        }
     }
 
-    pub fn to_scale(&self) -> f32 {
-        **self / 65.
+    /// Smaller asteroids are harder to hit, so they are worth more.
+    pub fn points(&self) -> u32 {
+        match self {
+            AsteroidSize::Large => 20,
+            AsteroidSize::Medium => 50,
+            AsteroidSize::Small => 100,
+        }
     }
 }
 
+/// Points of an asteroid's jagged outline, turned into a lyon shape when it gets drawn.
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct Outline(pub Vec<Vec2>);
+
+#[derive(Component)]
+pub struct Collidable;
+
+#[derive(Component, Deref, DerefMut)]
+pub struct Size(pub f32);
+
 #[derive(Component)]
 pub struct Explosion;
 
@@ -91,16 +149,123 @@ pub struct UI;
 pub struct ScoreUI;
 
 #[derive(Component)]
-pub struct UFO;
+pub struct LivesUI;
 
 #[derive(Component)]
-pub struct ShipBullet;
+pub struct SpectatorUI;
 
 #[derive(Component)]
-pub struct UfoBullet;
+pub struct BossHealthUI;
+
+#[derive(Component)]
+pub struct PauseMenuUI;
+
+/// Ordered the way the buttons are shown, top to bottom.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PauseMenuButton {
+    Resume,
+    Restart,
+    Controls,
+    Quit,
+}
+
+#[derive(Component)]
+pub struct ControlsUI;
+
+/// Ordered the way the buttons are shown, top to bottom.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ControlsButton {
+    Rebind(Action),
+    Back,
+}
+
+/// Shows which keys are bound to an action on the controls screen.
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct BindingText(pub Action);
+
+/// The spreadsheet that covers the game while the boss key is down.
+#[derive(Component)]
+pub struct BossScreenUI;
+
+/// The filled part of the boss's health bar.
+#[derive(Component)]
+pub struct BossHealthBar;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Component)]
+pub struct UFO;
+
+/// The big saucer is an easy target, the small one is worth a lot more.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UfoKind {
+    Large,
+    Small,
+}
+
+impl UfoKind {
+    pub fn size(&self) -> Size {
+        match self {
+            UfoKind::Large => Size(50.),
+            UfoKind::Small => Size(25.),
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            UfoKind::Large => 200,
+            UfoKind::Small => 1000,
+        }
+    }
+}
+
+/// The core of the boss. It shrugs off bullets until all of its segments are destroyed.
+#[derive(Component)]
+pub struct Boss;
+
+/// Everything the boss started with, core and segments together, so its health bar keeps
+/// shrinking as segments are destroyed.
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct BossMaxHealth(pub u32);
+
+/// A destructible piece of the boss, kept this far from the core.
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct BossSegment(pub Vec3);
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+/// What the boss fires the next time its [`FiringTimer`] finishes.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossAttack {
+    /// A fan of bullets at the ship.
+    Aimed,
+    /// Bullets in every direction.
+    Ring,
+    /// A bullet at the ship from every segment left.
+    Volley,
+}
+
+impl BossAttack {
+    pub fn next(&self) -> Self {
+        match self {
+            BossAttack::Aimed => BossAttack::Ring,
+            BossAttack::Ring => BossAttack::Volley,
+            BossAttack::Volley => BossAttack::Aimed,
+        }
+    }
+}
 
 #[derive(Component)]
-pub struct ShipFireAudio;
+pub struct ShipBullet;
 
 #[derive(Component)]
-pub struct UfoFireAudio;
+pub struct UfoBullet;
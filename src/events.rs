@@ -5,4 +5,15 @@ use bevy::ecs::event::Event;
 pub struct ExplosionEvent(pub Position);
 
 #[derive(Event)]
-pub struct ScoreEvent(pub u16);
+pub struct ScoreEvent(pub u32);
+
+/// A bullet fired by the ship destroyed something, or damaged the boss.
+#[derive(Event)]
+pub enum HitEvent {
+    Asteroid,
+    Ufo,
+    Boss,
+}
+
+#[derive(Event)]
+pub struct ShipDestroyedEvent;
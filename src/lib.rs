@@ -1,188 +1,370 @@
-mod components;
+pub mod bindings;
+pub mod components;
 pub mod events;
+pub mod options;
+pub mod presentation;
+pub mod replay;
 pub mod resources;
-mod states;
+pub mod simulation;
+pub mod states;
 mod systems;
+pub mod training;
 
-use bevy::prelude::*;
-use bevy_prototype_lyon::plugin::ShapePlugin;
-use bot::Bot;
-use events::{ExplosionEvent, ScoreEvent};
-use resources::{AsteroidCount, BeforeBossState, Countdown, HighScore, Score, UfoTimer, WorldSize};
-use states::GameState;
+use std::path::PathBuf;
+
+use bevy::{input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use bot::individual::Individual;
+use options::Options;
+use presentation::PresentationPlugin;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use replay::Replay;
+use resources::{
+    BossKeyUrl, BotPilot, GameRng, ReplayPlayback, Score, SelectedIndividual, StorageDirectories,
+    TrainingFeed, TrainingSnapshot, WorldSize,
+};
+use simulation::SimulationPlugin;
+use states::{GameState, SpectatorState};
 
 const GET_READY_TIME: f32 = 4.;
+const STARTING_LIVES: u8 = 3;
+const RESPAWN_TIME: f32 = 2.;
 
-pub fn run() {
+pub fn run(options: &Options) {
     App::new()
-        .add_plugins((DefaultPlugins, ShapePlugin, Game::new()))
+        .add_plugins((
+            DefaultPlugins,
+            SimulationPlugin {
+                tick_rate: options.tick_rate,
+            },
+            PresentationPlugin,
+        ))
+        .add_plugins(OptionsPlugin(options.clone()))
         .run();
 }
 
-struct Game {
-    bot: Bot,
+/// How a headless game ended.
+pub struct HeadlessGame {
+    pub score: u32,
+    pub seed: u64,
+}
+
+/// Plays a single game without a window, GPU or audio device and returns once it is over. Every
+/// frame advances the game by exactly one tick, so it runs as fast as the CPU allows.
+pub fn run_headless(options: &Options) -> HeadlessGame {
+    let mut app = headless_app(options, StorageDirectories::default());
+
+    play_until_game_over(&mut app);
+
+    HeadlessGame {
+        score: **app.world.resource::<Score>(),
+        seed: app.world.resource::<GameRng>().seed(),
+    }
 }
 
-impl Game {
-    pub fn new() -> Self {
-        let bot = Bot::new(8);
+fn headless_app(options: &Options, storage_directories: StorageDirectories) -> App {
+    let mut app = App::new();
 
-        Self { bot }
+    app.insert_resource(storage_directories)
+        .add_plugins((
+            MinimalPlugins,
+            InputPlugin,
+            SimulationPlugin {
+                tick_rate: options.tick_rate,
+            },
+            HeadlessPlugin,
+        ))
+        .add_plugins(OptionsPlugin(options.clone()));
+
+    // a replay brings its own timestep, and a frame has to be exactly one tick of it
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+
+    app.finish();
+    app.cleanup();
+
+    app
+}
+
+fn play_until_game_over(app: &mut App) {
+    while *app.world.resource::<State<GameState>>().get() != GameState::GameOver {
+        app.update();
     }
 }
 
-impl Plugin for Game {
+/// Trains bots in the background while the current champion flies the ship in a window, for
+/// watching how training is going.
+pub fn run_spectator(options: &Options) {
+    let feed = TrainingFeed::default();
+    let training_feed = feed.clone();
+    let training_options = options.clone();
+
+    std::thread::spawn(move || {
+        training::train(&training_options, |bot, champion| {
+            let mut population = bot.population.clone();
+            population.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+            *training_feed.lock().unwrap() = TrainingSnapshot {
+                generation: bot.generation_count,
+                champion: Some(champion.clone()),
+                population,
+            };
+        });
+    });
+
+    App::new()
+        .insert_resource(feed)
+        .add_plugins((
+            DefaultPlugins,
+            SimulationPlugin {
+                tick_rate: options.tick_rate,
+            },
+            PresentationPlugin,
+            SpectatorPlugin,
+        ))
+        .add_plugins(OptionsPlugin(options.clone()))
+        .run();
+}
+
+/// Inserts the resources picked on the command line, after the plugins have set their defaults.
+struct OptionsPlugin(Options);
+
+impl Plugin for OptionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ExplosionEvent>();
-        app.add_event::<ScoreEvent>();
-
-        app.insert_resource(WorldSize(1920., 1080.));
-        app.insert_resource(AsteroidCount(10));
-        app.insert_resource(BeforeBossState(GameState::Starting));
-        app.insert_resource(Countdown(Timer::from_seconds(
-            GET_READY_TIME,
-            TimerMode::Once,
-        )));
-        app.insert_resource(Score(0));
-        app.insert_resource(HighScore(0));
-        app.insert_resource(UfoTimer(Timer::from_seconds(15., TimerMode::Once)));
-
-        app.insert_state(GameState::Starting);
+        let mut rng = self.0.seed.map(GameRng::new).unwrap_or_default();
+
+        if let Some(path) = &self.0.champion {
+            app.insert_resource(BotPilot(load_champion(path.clone(), rng.seed())));
+        } else if self.0.bot {
+            let mut bot_rng = ChaCha8Rng::seed_from_u64(rng.seed());
+
+            app.insert_resource(BotPilot(Individual::new(&mut bot_rng)));
+        }
+
+        if let Some(path) = &self.0.replay {
+            let storage_directories = app.world.resource::<StorageDirectories>();
+
+            match load_replay(path.clone(), storage_directories) {
+                Ok(replay) => {
+                    rng = GameRng::new(replay.seed);
 
+                    app.insert_resource(WorldSize(replay.world_size.0, replay.world_size.1));
+                    app.insert_resource(Time::<Fixed>::from_duration(replay.timestep));
+                    app.insert_resource(ReplayPlayback::new(replay));
+                }
+                Err(error) => {
+                    eprintln!(
+                        "There was an error loading the replay, playing a new game: {error:?}"
+                    )
+                }
+            }
+        }
+
+        if let Some(url) = &self.0.boss_key_url {
+            app.insert_resource(BossKeyUrl(url.clone()));
+        }
+
+        app.insert_resource(rng);
+    }
+}
+
+fn load_champion(path: Option<PathBuf>, seed: u64) -> Individual {
+    let loaded = path
+        .or_else(training::champion_path)
+        .ok_or_else(|| eyre::eyre!("there is no data directory"))
+        .and_then(|path| Individual::load(&path));
+
+    loaded.unwrap_or_else(|error| {
+        eprintln!("There was an error loading the champion, flying an untrained bot: {error:?}");
+        Individual::new(&mut ChaCha8Rng::seed_from_u64(seed))
+    })
+}
+
+fn load_replay(
+    path: Option<PathBuf>,
+    storage_directories: &StorageDirectories,
+) -> eyre::Result<Replay> {
+    let path = path
+        .or_else(|| storage_directories.replay_path())
+        .ok_or_else(|| eyre::eyre!("there is no data directory"))?;
+
+    Replay::load(&path)
+}
+
+struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
         app.add_systems(
-            Startup,
-            (
-                systems::camera_systems::add_camera,
-                systems::camera_systems::add_camera_border,
-                systems::ui::display_score.after(systems::shared_systems::load_high_score),
-                systems::shared_systems::load_high_score,
-                set_speed,
-            ),
+            OnEnter(GameState::Starting),
+            (systems::headless_systems::skip_title_screen,),
         );
+    }
+}
+
+struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_state(SpectatorState::Watching);
+        app.init_resource::<SelectedIndividual>();
 
         app.add_systems(
-            OnEnter(GameState::Starting),
+            OnEnter(SpectatorState::Watching),
             (
-                systems::asteroid_systems::spawn_asteroids,
-                systems::ui::title_screen,
+                systems::spectator_systems::fly_selected,
+                systems::ui::display_spectator_overlay,
             ),
         );
 
         app.add_systems(
-            OnExit(GameState::Starting),
+            OnExit(SpectatorState::Watching),
             (
-                systems::shared_systems::reset_game,
-                systems::shared_systems::reset_ui,
+                systems::spectator_systems::stop_flying,
+                systems::ui::remove_spectator_overlay,
             ),
         );
 
         app.add_systems(
-            OnExit(GameState::Playing),
-            (systems::shared_systems::save_high_score,),
+            OnEnter(GameState::Starting),
+            (systems::spectator_systems::start_next_game,)
+                .run_if(in_state(SpectatorState::Watching)),
         );
 
         app.add_systems(
             OnEnter(GameState::GetReady),
-            (
-                systems::ship_systems::add_player,
-                systems::asteroid_systems::spawn_asteroids.after(systems::ship_systems::add_player),
-                systems::shared_systems::update_positions
-                    .after(systems::asteroid_systems::spawn_asteroids),
-                systems::shared_systems::reset_countdown,
-                systems::ui::get_ready_screen,
-                systems::bullet_systems::delete_all_bullets,
-            ),
+            (systems::spectator_systems::fly_selected,).run_if(in_state(SpectatorState::Watching)),
         );
 
         app.add_systems(
-            OnExit(GameState::GetReady),
-            (
-                systems::shared_systems::reset_ui,
-                systems::ufo_systems::set_ufo_spawn_timer,
-            ),
+            OnEnter(GameState::GameOver),
+            (systems::spectator_systems::start_next_game,)
+                .run_if(in_state(SpectatorState::Watching)),
         );
 
         app.add_systems(
             Update,
             (
+                systems::spectator_systems::toggle_watching,
                 (
-                    systems::shared_systems::apply_velocity,
-                    systems::shared_systems::update_positions,
-                    systems::shared_systems::wraparound_entities,
-                    systems::shared_systems::transition_states,
-                )
-                    .run_if(in_state(GameState::Starting)),
-                (
-                    systems::shared_systems::tick_countdown,
-                    systems::ui::update_get_ready_screen,
-                    systems::shared_systems::transition_from_get_ready_to_playing,
-                    systems::explosion::update_explosion,
-                    systems::explosion::remove_explosion,
-                    systems::ui::update_score_ui,
-                )
-                    .run_if(in_state(GameState::GetReady)),
-                (
-                    systems::ship_systems::change_thruster_colors,
-                    (
-                        systems::shared_systems::wraparound_entities,
-                        systems::ship_systems::input_rotate_ship,
-                        systems::ship_systems::rotate_ship,
-                        systems::ship_systems::input_thrust_ship,
-                        systems::ship_systems::apply_thrust,
-                        systems::shared_systems::apply_velocity,
-                        systems::ship_systems::input_firing,
-                        systems::bullet_systems::ship_fire_bullet,
-                        systems::bullet_systems::delete_expired_bullets,
-                        // systems::debug_systems::visualize_size,
-                        systems::ship_systems::handle_ship_collisions,
-                        systems::asteroid_systems::handle_collisions,
-                        systems::explosion::handle_explosion_event,
-                        systems::explosion::remove_explosion,
-                        systems::explosion::update_explosion,
-                        systems::shared_systems::update_positions,
-                    )
-                        // this chain tells the above systems that they need to run in order
-                        .chain(),
-                    systems::shared_systems::transition_from_playing_to_game_over,
-                    systems::shared_systems::update_scores,
-                    systems::ui::update_score_ui,
-                    systems::asteroid_systems::end_level,
-                    systems::ship_systems::teleport_ship,
-                    systems::ufo_systems::ufo_spawn_timer_update,
-                    systems::ufo_systems::spawn_ufo,
-                    systems::ufo_systems::update_velocity,
-                    systems::ufo_systems::handle_ufo_bullet_collisions,
-                    systems::ufo_systems::update,
-                    systems::bullet_systems::ufo_fire_bullet,
-                    systems::ship_systems::handle_ship_bullet_collisions
-                        .after(systems::ship_systems::change_thruster_colors),
-                )
-                    .run_if(in_state(GameState::Playing)),
-                (
-                    systems::shared_systems::transition_states,
-                    systems::explosion::update_explosion,
-                    systems::explosion::remove_explosion,
+                    systems::spectator_systems::cycle_individuals
+                        .run_if(resource_exists::<BotPilot>),
+                    systems::ui::update_spectator_overlay,
                 )
-                    .run_if(in_state(GameState::GameOver)),
-                (systems::shared_systems::to_from_boss,),
+                    .chain()
+                    .run_if(in_state(SpectatorState::Watching)),
             ),
         );
+    }
+}
 
-        app.add_systems(
-            OnEnter(GameState::GameOver),
-            (systems::ui::game_over_screen,),
+#[cfg(test)]
+mod tests {
+    use bevy::input::InputSystem;
+    use clap::Parser;
+
+    use super::*;
+    use crate::resources::ReplayRecording;
+
+    /// Holds fire the whole game and turns now and then, so the recording has runs of different
+    /// input.
+    fn fly(mut keyboard_input: ResMut<ButtonInput<KeyCode>>, mut frame: Local<u32>) {
+        *frame += 1;
+
+        keyboard_input.press(KeyCode::Space);
+        if *frame % 80 < 40 {
+            keyboard_input.press(KeyCode::ArrowLeft);
+        } else {
+            keyboard_input.release(KeyCode::ArrowLeft);
+        }
+    }
+
+    /// Frames played while the ship can fly, which should each be one tick.
+    #[derive(Resource, Default)]
+    struct PlayingFrames(u64);
+
+    fn count_playing_frames(mut frames: ResMut<PlayingFrames>) {
+        frames.0 += 1;
+    }
+
+    /// Hides the game behind the boss key and brings it back a few frames later.
+    fn press_boss_key(mut keyboard_input: ResMut<ButtonInput<KeyCode>>, mut frame: Local<u32>) {
+        *frame += 1;
+
+        if *frame == 1 || *frame == 10 {
+            keyboard_input.press(KeyCode::KeyB);
+        } else {
+            keyboard_input.release(KeyCode::KeyB);
+        }
+    }
+
+    #[test]
+    fn a_recorded_game_plays_back_the_same_way() {
+        let directory = std::env::temp_dir().join(format!("asteroids-{}", std::process::id()));
+        // keeps the replay and the high score out of the real config and data directories
+        let storage_directories = StorageDirectories {
+            config: Some(directory.join("config")),
+            data: Some(directory.join("data")),
+        };
+
+        let options = Options::parse_from([
+            "asteroids",
+            "--headless",
+            "--seed",
+            "7",
+            "--tick-rate",
+            "50",
+        ]);
+        let mut app = headless_app(&options, storage_directories.clone());
+        app.add_systems(PreUpdate, fly.after(InputSystem));
+        play_until_game_over(&mut app);
+
+        let score = **app.world.resource::<Score>();
+        let path = storage_directories.replay_path().unwrap();
+        let replay = Replay::load(&path).unwrap();
+
+        assert!(score > 0);
+        assert_eq!(replay.score, score);
+        assert!(replay.inputs.len() > 1);
+        assert!(!app.world.contains_resource::<ReplayRecording>());
+
+        // leaving the game over screen for the boss key and back neither resets the game nor
+        // saves the replay again
+        app.add_systems(PreUpdate, press_boss_key.after(InputSystem));
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::GameOver
         );
+        assert_eq!(**app.world.resource::<Score>(), score);
+        assert_eq!(Replay::load(&path).unwrap(), replay);
 
-        app.add_systems(
-            OnExit(GameState::GameOver),
-            (
-                systems::shared_systems::reset_ui,
-                systems::shared_systems::reset_game,
-            ),
+        // played back at the default tick rate, so the replay's own timestep has to be used
+        let options = Options::parse_from([
+            "asteroids",
+            "--headless",
+            "--replay",
+            path.to_str().unwrap(),
+        ]);
+        let mut app = headless_app(&options, storage_directories);
+        app.init_resource::<PlayingFrames>().add_systems(
+            Update,
+            count_playing_frames
+                .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Boss))),
         );
-    }
-}
+        play_until_game_over(&mut app);
+
+        let playback = app.world.resource::<ReplayPlayback>();
+
+        // one frame per tick, however many ticks a second the replay was recorded at
+        assert_eq!(app.world.resource::<PlayingFrames>().0, playback.ticks);
 
-fn set_speed(mut time: ResMut<Time<Virtual>>) {
-    time.set_relative_speed(1.0);
+        assert_eq!(playback.ticks, replay.ticks);
+        assert_eq!(**app.world.resource::<Score>(), replay.score);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
 }
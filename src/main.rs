@@ -1,5 +1,18 @@
-use bevy_asteroids::run;
+use bevy_asteroids::{options::Options, run, run_headless, run_spectator, training::run_training};
+use clap::Parser;
 
 fn main() {
-    run();
+    let options = Options::parse();
+
+    if options.spectate {
+        run_spectator(&options);
+    } else if options.train {
+        run_training(&options);
+    } else if options.headless {
+        let game = run_headless(&options);
+
+        println!("Game over, score: {}, seed: {}", game.score, game.seed);
+    } else {
+        run(&options);
+    }
 }
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use bot::config::MIN_POPULATION_SIZE;
+use clap::{builder::RangedU64ValueParser, Parser, ValueEnum};
+
+use crate::simulation::DEFAULT_TICK_RATE;
+
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Asteroids in Bevy")]
+pub struct Options {
+    /// Play without a window, GPU or audio device and exit when the game is over
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Seed for all gameplay randomness, a random one is picked when it isn't set
+    #[arg(long, env = "ASTEROIDS_SEED")]
+    pub seed: Option<u64>,
+
+    /// How many times per second the game is simulated, independent of the frame rate
+    #[arg(long, default_value_t = DEFAULT_TICK_RATE, value_parser = parse_tick_rate)]
+    pub tick_rate: f64,
+
+    /// Let a bot fly the ship instead of the keyboard
+    #[arg(long)]
+    pub bot: bool,
+
+    /// Evolve a population of bots by having them play headless games, then exit
+    #[arg(long)]
+    pub train: bool,
+
+    /// Train in the background while watching the best bot so far play
+    #[arg(long)]
+    pub spectate: bool,
+
+    /// How many generations to train for
+    #[arg(long, default_value_t = 100)]
+    pub generations: u64,
+
+    /// How many individuals make up each generation [default: 8]
+    #[arg(
+        long,
+        value_parser = RangedU64ValueParser::<usize>::new().range(MIN_POPULATION_SIZE as u64..)
+    )]
+    pub population_size: Option<usize>,
+
+    /// How the individuals that parent each new generation are picked [default: truncation]
+    #[arg(long, value_enum)]
+    pub selection: Option<Selection>,
+
+    /// How many individuals compete in each tournament when selecting by tournament [default: 3]
+    #[arg(long)]
+    pub tournament_size: Option<usize>,
+
+    /// How many of the best individuals always survive, whatever the selection [default: 0]
+    #[arg(long)]
+    pub elites: Option<usize>,
+
+    /// How two parents' weights are combined into a child's [default: single-point]
+    #[arg(long, value_enum)]
+    pub crossover: Option<CrossoverArg>,
+
+    /// Also write each generation's training stats as JSON lines, next to the CSV
+    #[arg(long)]
+    pub metrics_json_lines: bool,
+
+    /// Carry on training from a checkpoint, the last one saved if no path is given
+    #[arg(long, value_name = "PATH")]
+    pub resume: Option<Option<PathBuf>>,
+
+    /// Fly the ship with a trained bot, the champion of the last training run if no path is given
+    #[arg(long, value_name = "PATH")]
+    pub champion: Option<Option<PathBuf>>,
+
+    /// Watch a game saved at game over again, the last one if no path is given
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["bot", "champion", "train", "spectate"]
+    )]
+    pub replay: Option<Option<PathBuf>>,
+
+    /// Also open this page in a browser when the boss key is pressed
+    #[arg(long, env = "ASTEROIDS_BOSS_KEY_URL", value_name = "URL")]
+    pub boss_key_url: Option<String>,
+}
+
+impl Options {
+    /// The flags given for shaping a new population, which resuming training ignores.
+    pub fn population_flags(&self) -> Vec<&'static str> {
+        [
+            ("--population-size", self.population_size.is_some()),
+            ("--selection", self.selection.is_some()),
+            ("--tournament-size", self.tournament_size.is_some()),
+            ("--elites", self.elites.is_some()),
+            ("--crossover", self.crossover.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(flag, given)| given.then_some(flag))
+        .collect()
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// The best individuals plus a few random others
+    Truncation,
+    /// The best of a few randomly picked individuals, again and again
+    Tournament,
+    /// Random individuals, weighted by score
+    Roulette,
+    /// Random individuals, weighted by rank
+    Rank,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossoverArg {
+    /// One parent's weights up to a random point, then the other's
+    SinglePoint,
+    /// Each weight from either parent at random
+    Uniform,
+    /// Each weight a random mix of both parents'
+    Blend,
+    /// Each weight from either parent, favouring the fitter one
+    FitnessWeighted,
+}
+
+fn parse_tick_rate(value: &str) -> Result<f64, String> {
+    let tick_rate: f64 = value.parse().map_err(|error| format!("{error}"))?;
+
+    if tick_rate.is_finite() && tick_rate > 0. {
+        Ok(tick_rate)
+    } else {
+        Err("the tick rate has to be a number above 0".to_string())
+    }
+}
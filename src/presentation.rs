@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::plugin::ShapePlugin;
+
+use crate::{
+    components::{ControlsButton, PauseMenuButton},
+    resources::{Lives, MenuFocus, Rebinding},
+    states::{ControlsState, GameState},
+    systems,
+};
+
+/// Meshes, lyon shapes, audio and UI screens. Visuals are attached to the entities that the
+/// [`SimulationPlugin`](crate::simulation::SimulationPlugin) spawns as their components are added.
+pub struct PresentationPlugin;
+
+impl Plugin for PresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ShapePlugin);
+
+        app.init_resource::<Rebinding>();
+        app.init_resource::<MenuFocus<PauseMenuButton>>();
+        app.init_resource::<MenuFocus<ControlsButton>>();
+
+        app.add_systems(
+            FixedFirst,
+            systems::shared_systems::store_previous_positions,
+        );
+
+        app.add_systems(
+            Startup,
+            (
+                systems::camera_systems::add_camera,
+                systems::camera_systems::add_camera_border,
+                systems::ui::display_score.after(systems::shared_systems::load_high_score),
+                systems::ui::display_lives,
+            ),
+        );
+
+        app.add_systems(OnEnter(GameState::Starting), (systems::ui::title_screen,));
+
+        app.add_systems(
+            OnExit(GameState::Starting),
+            (systems::shared_systems::reset_ui,),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::GetReady),
+            (systems::ui::get_ready_screen,),
+        );
+
+        app.add_systems(
+            OnExit(GameState::GetReady),
+            (systems::shared_systems::reset_ui,),
+        );
+
+        app.add_systems(
+            Update,
+            (
+                (
+                    systems::ship_systems::add_ship_visuals,
+                    systems::asteroid_systems::add_asteroid_visuals,
+                    systems::ufo_systems::add_ufo_visuals,
+                    systems::bullet_systems::add_bullet_visuals,
+                    systems::boss_systems::add_boss_visuals,
+                    systems::explosion::handle_explosion_event,
+                    systems::explosion::remove_explosion,
+                    systems::explosion::update_explosion,
+                    // systems::debug_systems::visualize_size,
+                    systems::shared_systems::update_positions,
+                    systems::ui::update_score_ui,
+                )
+                    .chain(),
+                (systems::ui::update_lives_ui,).run_if(resource_changed::<Lives>),
+                (systems::ui::update_get_ready_screen,).run_if(in_state(GameState::GetReady)),
+                (systems::ship_systems::change_thruster_colors,)
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Boss))),
+                (systems::ui::update_boss_health,).run_if(in_state(GameState::Boss)),
+                (
+                    systems::ui::move_menu_focus::<PauseMenuButton>,
+                    systems::ui::pause_menu_buttons,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Paused))
+                    .run_if(in_state(ControlsState::Closed)),
+                (systems::controls_systems::open_controls,)
+                    .run_if(in_state(GameState::Starting))
+                    .run_if(in_state(ControlsState::Closed)),
+                // a key pressed to rebind is taken before the menu keys can move or select with it
+                (
+                    systems::controls_systems::capture_rebinding,
+                    systems::ui::move_menu_focus::<ControlsButton>,
+                    systems::ui::controls_screen_buttons,
+                    systems::ui::update_controls_screen,
+                )
+                    .chain()
+                    .run_if(in_state(ControlsState::Open)),
+            ),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::Boss),
+            (systems::ui::display_boss_health,),
+        );
+
+        app.add_systems(OnExit(GameState::Boss), (systems::ui::remove_boss_health,));
+
+        app.add_systems(
+            OnEnter(GameState::Paused),
+            (
+                systems::ui::display_pause_menu,
+                systems::ship_systems::silence_thrusters,
+            ),
+        );
+
+        app.add_systems(OnExit(GameState::Paused), (systems::ui::remove_pause_menu,));
+
+        app.add_systems(
+            OnEnter(ControlsState::Open),
+            (systems::ui::display_controls_screen,),
+        );
+
+        app.add_systems(
+            OnExit(ControlsState::Open),
+            (
+                systems::ui::remove_controls_screen,
+                systems::controls_systems::stop_rebinding,
+            ),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::BossKey),
+            (
+                systems::ui::display_boss_screen,
+                systems::ship_systems::silence_thrusters,
+            ),
+        );
+
+        app.add_systems(
+            OnExit(GameState::BossKey),
+            (systems::ui::remove_boss_screen,),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (systems::ui::game_over_screen,),
+        );
+
+        app.add_systems(
+            OnExit(GameState::GameOver),
+            (systems::shared_systems::reset_ui,),
+        );
+    }
+}
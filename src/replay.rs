@@ -0,0 +1,118 @@
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+const THRUST: u8 = 1;
+const FIRING: u8 = 1 << 1;
+const TELEPORT: u8 = 1 << 2;
+
+/// What the player did on one tick: the rotate speed and the thrust, firing and teleport buttons
+/// packed into one byte.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickInput(pub f32, pub u8);
+
+impl TickInput {
+    pub fn new(rotate_speed: f32, thrust: bool, firing: bool, teleport: bool) -> Self {
+        let mut buttons = 0;
+
+        if thrust {
+            buttons |= THRUST;
+        }
+        if firing {
+            buttons |= FIRING;
+        }
+        if teleport {
+            buttons |= TELEPORT;
+        }
+
+        Self(rotate_speed, buttons)
+    }
+
+    pub fn rotate_speed(&self) -> f32 {
+        self.0
+    }
+
+    pub fn thrust(&self) -> bool {
+        self.1 & THRUST != 0
+    }
+
+    pub fn firing(&self) -> bool {
+        self.1 & FIRING != 0
+    }
+
+    pub fn teleport(&self) -> bool {
+        self.1 & TELEPORT != 0
+    }
+}
+
+/// Everything needed to play a game again exactly as it went: where the gameplay randomness was
+/// when the game started and the input of every tick after that.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    /// How many random numbers the seed had already given out when the game started.
+    pub word_position: u64,
+    pub world_size: (f32, f32),
+    /// How long each tick is.
+    pub timestep: Duration,
+    /// Ticks played up to game over, and the score then, to tell if a replay went the same way.
+    pub ticks: u64,
+    pub score: u32,
+    /// The input of every tick, as runs of the same input so holding a button takes one entry.
+    pub inputs: Vec<(u32, TickInput)>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, ron::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, input: TickInput) {
+        self.ticks += 1;
+
+        match self.inputs.last_mut() {
+            Some((count, last)) if *last == input => *count += 1,
+            _ => self.inputs.push((1, input)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons_are_packed_into_one_byte() {
+        let input = TickInput::new(-0.5, true, false, true);
+
+        assert_eq!(input, TickInput(-0.5, THRUST | TELEPORT));
+        assert_eq!(input.rotate_speed(), -0.5);
+        assert!(input.thrust());
+        assert!(!input.firing());
+        assert!(input.teleport());
+    }
+
+    #[test]
+    fn held_input_is_pushed_as_runs() {
+        let idle = TickInput::default();
+        let firing = TickInput::new(0., false, true, false);
+        let turning = TickInput::new(1., false, true, false);
+        let mut replay = Replay::default();
+
+        for input in [idle, idle, firing, turning, turning, turning, idle] {
+            replay.push(input);
+        }
+
+        assert_eq!(replay.ticks, 7);
+        assert_eq!(
+            replay.inputs,
+            [(2, idle), (1, firing), (3, turning), (1, idle)]
+        );
+    }
+}
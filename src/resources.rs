@@ -1,19 +1,30 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
 use bevy::{
     ecs::system::Resource,
     math::{Vec2, Vec3},
     prelude::{Deref, DerefMut},
     time::Timer,
 };
-use rand::{thread_rng, Rng};
+use bot::{command::Command, individual::Individual};
+use directories::ProjectDirs;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-use crate::states::GameState;
+use crate::{
+    bindings::Action,
+    replay::{Replay, TickInput},
+    states::GameState,
+};
 
 #[derive(Resource, Debug)]
 pub struct WorldSize(pub f32, pub f32);
 
 impl WorldSize {
-    pub fn get_random_coords(&self) -> Vec3 {
-        let mut rng = thread_rng();
+    pub fn get_random_coords(&self, rng: &mut impl Rng) -> Vec3 {
         let half_width = self.0 * 0.5;
         let half_height = self.1 * 0.5;
 
@@ -24,8 +35,7 @@ impl WorldSize {
         )
     }
 
-    pub fn get_random_edge(&self) -> Vec3 {
-        let mut rng = thread_rng();
+    pub fn get_random_edge(&self, rng: &mut impl Rng) -> Vec3 {
         let random_edge: u8 = rng.gen_range(0..4);
         let half_width = self.0 * 0.5;
         let half_height = self.1 * 0.5;
@@ -53,14 +63,82 @@ impl From<&WorldSize> for Vec2 {
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct AsteroidCount(pub u8);
 
+/// The level being played, counting from 1.
+#[derive(Resource, Debug, Deref, DerefMut, Clone, Copy)]
+pub struct Level(pub u32);
+
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct Countdown(pub Timer);
 
 #[derive(Resource, Debug, Deref, DerefMut, Copy, Clone)]
 pub struct BeforeBossState(pub GameState);
 
+/// Where resuming from the pause menu goes back to.
+#[derive(Resource, Debug, Deref, DerefMut, Copy, Clone)]
+pub struct BeforePauseState(pub GameState);
+
+/// The action waiting for a new key on the controls screen.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Rebinding(pub Option<Action>);
+
+/// The button of a menu that the menu keys and d-pad have moved to, `None` until they're used.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct MenuFocus<T: Send + Sync + 'static>(pub Option<T>);
+
+impl<T: Send + Sync + 'static> Default for MenuFocus<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+/// A page to open in a browser on top of the boss screen. Nothing is opened without one.
+#[derive(Resource, Debug, Deref)]
+pub struct BossKeyUrl(pub String);
+
+/// Where the high score, controls and replay are kept. Either can be missing on a system without
+/// a home directory, and then nothing is saved there.
+#[derive(Resource, Debug, Clone)]
+pub struct StorageDirectories {
+    pub config: Option<PathBuf>,
+    pub data: Option<PathBuf>,
+}
+
+impl StorageDirectories {
+    pub fn bindings_path(&self) -> Option<PathBuf> {
+        self.config
+            .as_ref()
+            .map(|directory| directory.join("bindings.ron"))
+    }
+
+    pub fn high_score_path(&self) -> Option<PathBuf> {
+        self.data
+            .as_ref()
+            .map(|directory| directory.join("high_score"))
+    }
+
+    /// The last game the player finished.
+    pub fn replay_path(&self) -> Option<PathBuf> {
+        self.data
+            .as_ref()
+            .map(|directory| directory.join("replay.ron"))
+    }
+}
+
+impl Default for StorageDirectories {
+    fn default() -> Self {
+        let project_directory = ProjectDirs::from("com", "brooksbuilds", "bevy_asteroids");
+
+        Self {
+            config: project_directory
+                .as_ref()
+                .map(|directory| directory.config_dir().to_path_buf()),
+            data: project_directory.map(|directory| directory.data_dir().to_path_buf()),
+        }
+    }
+}
+
 #[derive(Resource, Debug, Deref, DerefMut, Clone, Copy)]
-pub struct Score(pub u16);
+pub struct Score(pub u32);
 
 impl From<Score> for String {
     fn from(value: Score) -> Self {
@@ -69,7 +147,7 @@ impl From<Score> for String {
 }
 
 #[derive(Resource, Debug, Deref, DerefMut, Clone, Copy)]
-pub struct HighScore(pub u16);
+pub struct HighScore(pub u32);
 
 impl From<HighScore> for String {
     fn from(value: HighScore) -> Self {
@@ -77,5 +155,199 @@ impl From<HighScore> for String {
     }
 }
 
+/// Ships left, counting the one flying.
+#[derive(Resource, Debug, Deref, DerefMut, Clone, Copy)]
+pub struct Lives(pub u8);
+
+/// How long after losing a ship before the next one can come in.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct RespawnTimer(pub Timer);
+
 #[derive(Resource, Debug, Deref, DerefMut, Clone)]
 pub struct UfoTimer(pub Timer);
+
+/// The only source of randomness for gameplay. Two games started with the same seed and fed
+/// the same inputs play out identically.
+#[derive(Resource, Debug, Deref, DerefMut, Clone)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Picks the seed up partway through, after it has given out `word_position` numbers.
+    pub fn resume(seed: u64, word_position: u128) -> Self {
+        let mut rng = Self::new(seed);
+
+        rng.rng.set_word_pos(word_position);
+        rng
+    }
+
+    pub fn word_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(thread_rng().gen())
+    }
+}
+
+/// The player's game so far, saved as a [`Replay`] at game over.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct ReplayRecording(pub Replay);
+
+/// Feeds the ship the input of a [`Replay`] instead of the keyboard.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    /// Ticks played since the game started, whether or not the replay had input left for them.
+    pub ticks: u64,
+    run: usize,
+    tick_in_run: u32,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            ticks: 0,
+            run: 0,
+            tick_in_run: 0,
+        }
+    }
+
+    pub fn rewind(&mut self) {
+        self.ticks = 0;
+        self.run = 0;
+        self.tick_in_run = 0;
+    }
+
+    /// Nothing is pressed once the recorded input runs out.
+    pub fn next_input(&mut self) -> TickInput {
+        self.ticks += 1;
+
+        let Some(&(count, input)) = self.replay.inputs.get(self.run) else {
+            return TickInput::default();
+        };
+
+        self.tick_in_run += 1;
+
+        if self.tick_in_run >= count {
+            self.run += 1;
+            self.tick_in_run = 0;
+        }
+
+        input
+    }
+
+    /// A replay that doesn't end on the same tick with the same score has gone out of sync.
+    pub fn ended_like_recorded(&self, score: u32) -> bool {
+        self.ticks == self.replay.ticks && score == self.replay.score
+    }
+}
+
+/// Hands control of the ship to a bot individual instead of the keyboard. The individual's play
+/// stats are gathered on every tick it plays.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct BotPilot(pub Individual);
+
+/// What the [`BotPilot`] decided to do on its last tick.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct BotCommands(pub Vec<Command>);
+
+/// The latest scored generation of a training run, shared with the thread doing the training.
+#[derive(Resource, Debug, Clone, Default, Deref)]
+pub struct TrainingFeed(pub Arc<Mutex<TrainingSnapshot>>);
+
+#[derive(Debug, Default)]
+pub struct TrainingSnapshot {
+    pub generation: u64,
+    /// The best individual of the whole run so far.
+    pub champion: Option<Individual>,
+    /// The latest generation, best first.
+    pub population: Vec<Individual>,
+}
+
+/// Who a spectator is watching: the champion at 0, otherwise that place in the population.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct SelectedIndividual(pub usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playback_steps_through_each_run_then_stops_pressing_anything() {
+        let firing = TickInput::new(0., false, true, false);
+        let turning = TickInput::new(-1., true, false, false);
+        let mut playback = ReplayPlayback::new(Replay {
+            ticks: 4,
+            inputs: vec![(1, firing), (3, turning)],
+            ..Default::default()
+        });
+
+        let inputs: Vec<TickInput> = (0..6).map(|_| playback.next_input()).collect();
+
+        assert_eq!(
+            inputs,
+            [
+                firing,
+                turning,
+                turning,
+                turning,
+                TickInput::default(),
+                TickInput::default()
+            ]
+        );
+        assert_eq!(playback.ticks, 6);
+    }
+
+    #[test]
+    fn rewound_playback_starts_over() {
+        let firing = TickInput::new(0., false, true, false);
+        let turning = TickInput::new(1., false, false, false);
+        let mut playback = ReplayPlayback::new(Replay {
+            ticks: 3,
+            inputs: vec![(2, firing), (1, turning)],
+            ..Default::default()
+        });
+
+        playback.next_input();
+        playback.next_input();
+        playback.rewind();
+
+        assert_eq!(playback.ticks, 0);
+        assert_eq!(playback.next_input(), firing);
+    }
+
+    #[test]
+    fn playback_ends_like_recorded_on_the_same_tick_with_the_same_score() {
+        let mut playback = ReplayPlayback::new(Replay {
+            ticks: 2,
+            score: 120,
+            inputs: vec![(2, TickInput::default())],
+            ..Default::default()
+        });
+
+        playback.next_input();
+        assert!(!playback.ended_like_recorded(120));
+
+        playback.next_input();
+        assert!(playback.ended_like_recorded(120));
+        assert!(!playback.ended_like_recorded(100));
+    }
+}
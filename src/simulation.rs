@@ -0,0 +1,329 @@
+use bevy::{ecs::schedule::apply_state_transition, prelude::*};
+
+use crate::{
+    bindings::InputBindings,
+    events::{ExplosionEvent, HitEvent, ScoreEvent, ShipDestroyedEvent},
+    resources::{
+        AsteroidCount, BeforeBossState, BeforePauseState, BotCommands, BotPilot, Countdown,
+        GameRng, HighScore, Level, Lives, ReplayPlayback, ReplayRecording, RespawnTimer, Score,
+        StorageDirectories, UfoTimer, WorldSize,
+    },
+    states::{ControlsState, GameState},
+    systems, GET_READY_TIME, RESPAWN_TIME, STARTING_LIVES,
+};
+
+pub const DEFAULT_TICK_RATE: f64 = 60.;
+
+/// Everything that makes the game play: movement, collisions, scoring and the game states.
+/// Nothing in here needs a window, GPU or audio device.
+///
+/// Gameplay runs in `FixedUpdate` at `tick_rate` ticks per second, so it plays the same no
+/// matter how fast frames are drawn. Only reading the keyboard happens every frame, and not at
+/// all when a [`BotPilot`] or a [`ReplayPlayback`] is flying the ship.
+pub struct SimulationPlugin {
+    pub tick_rate: f64,
+}
+
+impl Default for SimulationPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>();
+        app.add_event::<ScoreEvent>();
+        app.add_event::<HitEvent>();
+        app.add_event::<ShipDestroyedEvent>();
+
+        app.insert_resource(WorldSize(1920., 1080.));
+        app.insert_resource(AsteroidCount(systems::shared_systems::TITLE_ASTEROID_COUNT));
+        app.insert_resource(Level(1));
+        app.insert_resource(BeforeBossState(GameState::Starting));
+        app.insert_resource(BeforePauseState(GameState::Playing));
+        app.insert_resource(Countdown(Timer::from_seconds(
+            GET_READY_TIME,
+            TimerMode::Once,
+        )));
+        app.insert_resource(Score(0));
+        app.insert_resource(HighScore(0));
+        app.insert_resource(Lives(STARTING_LIVES));
+        app.insert_resource(RespawnTimer(Timer::from_seconds(
+            RESPAWN_TIME,
+            TimerMode::Once,
+        )));
+        app.insert_resource(UfoTimer(Timer::from_seconds(15., TimerMode::Once)));
+        app.init_resource::<GameRng>();
+        app.init_resource::<BotCommands>();
+        app.init_resource::<InputBindings>();
+        app.init_resource::<StorageDirectories>();
+        app.insert_resource(Time::<Fixed>::from_hz(self.tick_rate));
+
+        app.insert_state(GameState::Starting);
+        app.init_state::<ControlsState>();
+
+        app.add_systems(
+            Startup,
+            (
+                systems::shared_systems::load_high_score.run_if(not(resource_exists::<BotPilot>)),
+                systems::shared_systems::log_seed,
+                systems::shared_systems::load_input_bindings
+                    .run_if(not(resource_exists::<BotPilot>)),
+                set_speed,
+            ),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::Starting),
+            (
+                systems::asteroid_systems::spawn_asteroids,
+                systems::headless_systems::skip_title_screen
+                    .run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            OnExit(GameState::Starting),
+            (
+                systems::shared_systems::reset_game,
+                systems::replay_systems::start_recording.run_if(player_is_flying),
+                systems::replay_systems::start_playback.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            OnExit(GameState::Playing),
+            // a bot's scores aren't the player's high score, and neither are a replay's
+            (systems::shared_systems::save_high_score.run_if(player_is_flying),),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::Boss),
+            (systems::boss_systems::spawn_boss,),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GameState::Playing,
+                to: GameState::Boss,
+            },
+            (systems::bullet_systems::delete_all_bullets,),
+        );
+
+        app.add_systems(
+            OnExit(GameState::Boss),
+            (systems::shared_systems::save_high_score.run_if(player_is_flying),),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::BossKey),
+            (systems::shared_systems::pause_time,),
+        );
+
+        app.add_systems(
+            OnExit(GameState::BossKey),
+            (systems::shared_systems::unpause_time,),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::Paused),
+            (systems::shared_systems::pause_time,),
+        );
+
+        app.add_systems(
+            OnExit(GameState::Paused),
+            (systems::shared_systems::unpause_time,),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GameState::Paused,
+                to: GameState::GetReady,
+            },
+            (
+                systems::shared_systems::reset_game,
+                systems::replay_systems::start_recording.run_if(player_is_flying),
+                systems::replay_systems::start_playback.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GameState::Paused,
+                to: GameState::Starting,
+            },
+            (
+                systems::shared_systems::reset_game,
+                systems::shared_systems::reset_title_asteroids,
+            )
+                .chain(),
+        );
+
+        app.add_systems(
+            OnEnter(GameState::GetReady),
+            (
+                systems::ship_systems::add_player,
+                systems::asteroid_systems::spawn_asteroids.after(systems::ship_systems::add_player),
+                systems::shared_systems::reset_countdown,
+                systems::bullet_systems::delete_all_bullets,
+            ),
+        );
+
+        app.add_systems(
+            OnExit(GameState::GetReady),
+            (systems::ufo_systems::set_ufo_spawn_timer,),
+        );
+
+        // the game over screen can also be left for the boss key and come back, which mustn't
+        // save the replay again or reset the game
+        app.add_systems(
+            OnTransition {
+                from: GameState::Playing,
+                to: GameState::GameOver,
+            },
+            (
+                systems::replay_systems::save_replay.run_if(resource_exists::<ReplayRecording>),
+                systems::replay_systems::check_replay.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GameState::Boss,
+                to: GameState::GameOver,
+            },
+            (
+                systems::replay_systems::save_replay.run_if(resource_exists::<ReplayRecording>),
+                systems::replay_systems::check_replay.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GameState::GameOver,
+                to: GameState::GetReady,
+            },
+            (
+                systems::shared_systems::reset_game,
+                systems::replay_systems::start_recording.run_if(player_is_flying),
+                systems::replay_systems::start_playback.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        app.add_systems(
+            Update,
+            (
+                (systems::shared_systems::transition_states,)
+                    .run_if(in_state(GameState::Starting).or_else(in_state(GameState::GameOver)))
+                    .run_if(in_state(ControlsState::Closed)),
+                (
+                    systems::ship_systems::input_rotate_ship,
+                    systems::ship_systems::input_thrust_ship,
+                    systems::ship_systems::input_firing,
+                    systems::ship_systems::input_teleport_ship,
+                )
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Boss)))
+                    .run_if(player_is_flying),
+                (
+                    systems::shared_systems::to_from_boss,
+                    systems::shared_systems::toggle_pause,
+                )
+                    .run_if(in_state(ControlsState::Closed)),
+                (systems::controls_systems::handle_gamepad_connections,)
+                    .run_if(not(resource_exists::<BotPilot>)),
+            ),
+        );
+
+        app.add_systems(
+            FixedUpdate,
+            (
+                (
+                    systems::shared_systems::apply_velocity,
+                    systems::shared_systems::wraparound_entities,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Starting)),
+                (
+                    systems::shared_systems::tick_countdown,
+                    systems::shared_systems::transition_from_get_ready_to_playing,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GetReady)),
+                (
+                    (
+                        systems::shared_systems::wraparound_entities,
+                        systems::bot_systems::pilot_ship.run_if(resource_exists::<BotPilot>),
+                        systems::replay_systems::play_back_input
+                            .run_if(resource_exists::<ReplayPlayback>),
+                        systems::replay_systems::record_input
+                            .run_if(resource_exists::<ReplayRecording>),
+                        systems::ship_systems::rotate_ship,
+                        systems::ship_systems::apply_thrust,
+                        systems::shared_systems::apply_velocity,
+                        systems::bullet_systems::ship_fire_bullet,
+                        systems::bullet_systems::delete_expired_bullets,
+                        systems::ship_systems::handle_ship_collisions,
+                        systems::asteroid_systems::handle_collisions,
+                    )
+                        // this chain tells the above systems that they need to run in order
+                        .chain(),
+                    (
+                        systems::ship_systems::teleport_ship,
+                        systems::ufo_systems::ufo_spawn_timer_update
+                            .run_if(in_state(GameState::Playing)),
+                        systems::ufo_systems::spawn_ufo.run_if(in_state(GameState::Playing)),
+                        systems::ufo_systems::update_velocity,
+                        systems::ufo_systems::handle_ufo_bullet_collisions,
+                        systems::ufo_systems::update,
+                        systems::bullet_systems::ufo_fire_bullet,
+                        systems::ship_systems::handle_ship_bullet_collisions,
+                    )
+                        .chain(),
+                    (
+                        systems::boss_systems::steer_boss,
+                        systems::boss_systems::hold_boss_segments,
+                        systems::boss_systems::boss_attack,
+                        systems::boss_systems::handle_boss_collisions,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::Boss)),
+                    (
+                        systems::bot_systems::record_play_stats.run_if(resource_exists::<BotPilot>),
+                        systems::ship_systems::lose_life,
+                        systems::ship_systems::respawn_ship,
+                        systems::shared_systems::award_extra_lives,
+                        systems::shared_systems::update_scores,
+                        systems::shared_systems::update_high_score.run_if(player_is_flying),
+                        systems::asteroid_systems::end_level.run_if(in_state(GameState::Playing)),
+                        systems::boss_systems::end_boss_fight.run_if(in_state(GameState::Boss)),
+                        systems::shared_systems::transition_from_playing_to_game_over,
+                    )
+                        .chain(),
+                )
+                    // every tick has to play out the same way for a seed to be reproducible, so
+                    // nothing here is left for the scheduler to order
+                    .chain()
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Boss))),
+            ),
+        );
+
+        // change states on the tick that asked for it rather than on the next frame, otherwise
+        // the number of ticks spent in each state would depend on the frame rate
+        app.add_systems(FixedPostUpdate, apply_state_transition::<GameState>);
+    }
+}
+
+/// Neither a bot nor a replay is flying the ship.
+fn player_is_flying(
+    bot_pilot: Option<Res<BotPilot>>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+) -> bool {
+    bot_pilot.is_none() && replay_playback.is_none()
+}
+
+fn set_speed(mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(1.0);
+}
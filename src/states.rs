@@ -6,5 +6,27 @@ pub enum GameState {
     GetReady,
     Playing,
     GameOver,
+    /// Fighting the boss that turns up every few levels.
     Boss,
+    /// Hiding the game from a boss of the other kind.
+    BossKey,
+    /// Stopped mid game, with the pause menu up.
+    Paused,
+}
+
+/// Whether the controls screen is up, over the title screen or the pause menu.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Copy, Default)]
+pub enum ControlsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+/// Whether the ship is being flown by a bot from a training run in the background, for people
+/// to watch.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Copy, Default)]
+pub enum SpectatorState {
+    #[default]
+    Off,
+    Watching,
 }
@@ -1,17 +1,17 @@
 use crate::{
-    components::{Asteroid, Bullet, Collidable, Position, Ship, Size, Velocity},
-    events::{ExplosionEvent, ScoreEvent},
-    resources::{AsteroidCount, WorldSize},
+    components::{
+        Asteroid, AsteroidSize, Bullet, Collidable, Outline, Position, PreviousPosition, Ship,
+        ShipBullet, Size, Velocity,
+    },
+    events::{ExplosionEvent, HitEvent, ScoreEvent},
+    resources::{AsteroidCount, GameRng, Level, WorldSize},
     states::GameState,
 };
+
+use super::boss_systems::boss_level;
 use bevy::{prelude::*, render::color};
-use bevy_prototype_lyon::{
-    draw::Stroke,
-    entity::ShapeBundle,
-    geometry::GeometryBuilder,
-    shapes::{self, Polygon},
-};
-use rand::{rngs::ThreadRng, thread_rng, Rng};
+use bevy_prototype_lyon::{draw::Stroke, entity::ShapeBundle, geometry::GeometryBuilder, shapes};
+use rand::Rng;
 use std::ops::Deref;
 
 pub fn spawn_asteroids(
@@ -19,13 +19,13 @@ pub fn spawn_asteroids(
     world_size: Res<WorldSize>,
     ship_query: Query<&Position, With<Ship>>,
     desired_asteroids: Res<AsteroidCount>,
+    mut rng: ResMut<GameRng>,
 ) {
     let ship_position = ship_query.iter().next();
 
     let world_size: Vec2 = world_size.deref().into();
-    let mut rng = thread_rng();
     let mut created_asteroids = 0;
-    let size = Size::from_scale(2.);
+    let size = AsteroidSize::Large.radius();
 
     let half_width = world_size.x / 2.;
     let half_height = world_size.y / 2.;
@@ -40,12 +40,19 @@ pub fn spawn_asteroids(
         if let Some(ship_position) = ship_position {
             let distance_to_ship = position.distance(**ship_position);
 
-            if distance_to_ship < *size * 2. {
+            if distance_to_ship < size * 2. {
                 continue;
             }
         };
 
-        spawn_asteroid(&mut commands, 2., &mut rng, position);
+        let direction = rng.gen_range(0.0..std::f32::consts::TAU);
+        spawn_asteroid(
+            &mut commands,
+            AsteroidSize::Large,
+            &mut **rng,
+            position,
+            direction,
+        );
         created_asteroids += 1;
 
         if created_asteroids >= **desired_asteroids {
@@ -54,79 +61,117 @@ pub fn spawn_asteroids(
     }
 }
 
-fn spawn_asteroid(commands: &mut Commands, scale: f32, rng: &mut ThreadRng, position: Position) {
-    // scale = 2.0 == 15
-    // scale = 1.0 == 30
-    // scale = 0.5 == 60
-    let asteroid_speed = rng.gen_range(29.0..31.0) / scale;
-    let (shape, size) = create_asteroid_shape(scale);
-    let velocity =
-        Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)).extend(0.) * asteroid_speed;
+/// `direction` is the angle, in radians, that the asteroid heads off at.
+fn spawn_asteroid(
+    commands: &mut Commands,
+    asteroid_size: AsteroidSize,
+    rng: &mut impl Rng,
+    position: Position,
+    direction: f32,
+) {
+    let asteroid_speed = rng.gen_range(asteroid_size.speed_range());
+    let outline = create_asteroid_shape(asteroid_size.radius(), rng);
+    let velocity = Vec2::from_angle(direction).extend(0.) * asteroid_speed;
 
     commands.spawn((
-        ShapeBundle {
-            path: GeometryBuilder::build_as(&shape),
-            ..Default::default()
-        },
-        Stroke::new(color::Color::ANTIQUE_WHITE, 2.),
+        outline,
         Asteroid,
+        asteroid_size,
         position,
         Velocity(velocity),
         Collidable,
-        size,
+        Size(asteroid_size.radius()),
     ));
 }
 
-fn create_asteroid_shape(scale: f32) -> (Polygon, Size) {
-    let mut rng = thread_rng();
+/// Fans the pieces of a shot asteroid out evenly over its tier's spread.
+fn split_direction(heading: f32, spread: f32, piece: usize, piece_count: usize) -> f32 {
+    if piece_count < 2 {
+        return heading;
+    }
+
+    heading - spread / 2. + spread * piece as f32 / (piece_count - 1) as f32
+}
+
+fn create_asteroid_shape(radius: f32, rng: &mut impl Rng) -> Outline {
     let mut points = vec![];
     let point_count = 25.;
-    let size = Size::from_scale(scale);
 
     for i in 0..point_count as u8 {
         let angle = i as f32 * std::f32::consts::TAU / point_count;
         let mut point = Vec2::from_angle(angle);
 
-        point *= rng.gen_range(50.0..75.0);
-        point *= scale;
+        point *= rng.gen_range(radius * 0.75..radius * 1.15);
 
         points.push(point);
     }
 
-    (
-        shapes::Polygon {
-            points,
+    Outline(points)
+}
+
+pub fn add_asteroid_visuals(
+    mut commands: Commands,
+    asteroid_query: Query<(Entity, &Position, &Outline), Added<Asteroid>>,
+) {
+    for (asteroid, position, outline) in &asteroid_query {
+        let shape = shapes::Polygon {
+            points: outline.0.clone(),
             closed: true,
-        },
-        size,
-    )
+        };
+
+        commands.entity(asteroid).try_insert((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                spatial: SpatialBundle::from_transform(Transform::from_translation(**position)),
+                ..Default::default()
+            },
+            Stroke::new(color::Color::ANTIQUE_WHITE, 2.),
+            PreviousPosition(**position),
+        ));
+    }
 }
 
 pub fn handle_collisions(
-    asteroid_query: Query<(&Position, &Size, Entity), With<Asteroid>>,
-    bullet_query: Query<(&Position, &Size, Entity), With<Bullet>>,
+    asteroid_query: Query<(&Position, &Velocity, &AsteroidSize, Entity), With<Asteroid>>,
+    bullet_query: Query<(&Position, &Size, Entity, Has<ShipBullet>), With<Bullet>>,
     mut commands: Commands,
     mut explosion_event: EventWriter<ExplosionEvent>,
     mut score_event: EventWriter<ScoreEvent>,
+    mut hit_event: EventWriter<HitEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
-    for (bullet_position, bullet_size, bullet_entity) in bullet_query.iter() {
-        for (asteroid_position, asteroid_size, asteroid_entity) in asteroid_query.iter() {
-            if bullet_position.distance(**asteroid_position) > **bullet_size + **asteroid_size {
+    for (bullet_position, bullet_size, bullet_entity, is_ship_bullet) in bullet_query.iter() {
+        for (asteroid_position, asteroid_velocity, asteroid_size, asteroid_entity) in
+            asteroid_query.iter()
+        {
+            if bullet_position.distance(**asteroid_position)
+                > **bullet_size + asteroid_size.radius()
+            {
                 continue;
             }
             commands.entity(asteroid_entity).despawn();
             commands.entity(bullet_entity).despawn();
 
-            explosion_event.send(ExplosionEvent(asteroid_position.clone()));
-            score_event.send(ScoreEvent(1));
-            // create 2 asteroids
-
-            let mut rng = thread_rng();
+            explosion_event.send(ExplosionEvent(*asteroid_position));
+            if is_ship_bullet {
+                score_event.send(ScoreEvent(asteroid_size.points()));
+                hit_event.send(HitEvent::Asteroid);
+            }
 
-            let scale = asteroid_size.to_scale() / 2.;
-            if scale > 0.1 {
-                spawn_asteroid(&mut commands, scale, &mut rng, asteroid_position.clone());
-                spawn_asteroid(&mut commands, scale, &mut rng, asteroid_position.clone());
+            if let Some((piece_size, piece_count)) = asteroid_size.split() {
+                let heading = asteroid_velocity.y.atan2(asteroid_velocity.x);
+                let spread = asteroid_size.split_spread();
+
+                for piece in 0..piece_count {
+                    let direction = split_direction(heading, spread, piece, piece_count);
+                    spawn_asteroid(
+                        &mut commands,
+                        piece_size,
+                        &mut **rng,
+                        *asteroid_position,
+                        direction,
+                    );
+                }
             }
 
             break; // Each bullet can only hit one asteroid
@@ -137,6 +182,7 @@ pub fn handle_collisions(
 pub fn end_level(
     asteroids_query: Query<&Asteroid>,
     mut asteroid_count: ResMut<AsteroidCount>,
+    mut level: ResMut<Level>,
     current_state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
@@ -145,8 +191,14 @@ pub fn end_level(
     }
 
     asteroid_count.0 += 1;
+    let cleared = **level;
+    **level += 1;
 
     if let GameState::Playing = current_state.get() {
-        next_state.set(GameState::GetReady);
+        if boss_level(cleared) {
+            next_state.set(GameState::Boss);
+        } else {
+            next_state.set(GameState::GetReady);
+        }
     }
 }
@@ -0,0 +1,287 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{draw::Stroke, entity::ShapeBundle, geometry::GeometryBuilder, shapes};
+
+use crate::{
+    components::{
+        Boss, BossAttack, BossMaxHealth, BossSegment, Bullet, Collidable, FiringTimer, Health,
+        Position, PreviousPosition, Ship, ShipBullet, Size, Velocity,
+    },
+    events::{ExplosionEvent, HitEvent, ScoreEvent},
+    resources::{Level, WorldSize},
+    states::GameState,
+};
+
+use super::bullet_systems::spawn_ufo_bullet;
+
+/// The boss turns up after clearing every this many levels.
+pub const BOSS_LEVEL_INTERVAL: u32 = 3;
+const BOSS_CORE_SIZE: f32 = 60.;
+const BOSS_CORE_HEALTH: u32 = 10;
+const BOSS_SEGMENT_COUNT: usize = 6;
+const BOSS_SEGMENT_SIZE: f32 = 30.;
+const BOSS_SEGMENT_HEALTH: u32 = 3;
+/// How far the segments sit from the middle of the core.
+const BOSS_SEGMENT_DISTANCE: f32 = 110.;
+const BOSS_SPEED: f32 = 80.;
+const BOSS_ATTACK_TIME: f32 = 1.2;
+const BOSS_BULLET_SPEED: f32 = 350.;
+const BOSS_BULLET_LIFETIME: Duration = Duration::from_millis(3000);
+const BOSS_FAN_BULLETS: usize = 3;
+const BOSS_FAN_SPREAD: f32 = 0.5;
+const BOSS_RING_BULLETS: usize = 12;
+const BOSS_SEGMENT_POINTS: u32 = 500;
+const BOSS_CORE_POINTS: u32 = 2000;
+
+// `is_multiple_of` needs a newer compiler than the rest of the game does
+#[allow(clippy::manual_is_multiple_of)]
+pub fn boss_level(level: u32) -> bool {
+    level % BOSS_LEVEL_INTERVAL == 0
+}
+
+/// The boss picks up where it was when coming back from the boss key or the pause menu, so this
+/// only spawns one if there isn't one already.
+pub fn spawn_boss(
+    mut commands: Commands,
+    world_size: Res<WorldSize>,
+    level: Res<Level>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    if !boss_query.is_empty() {
+        return;
+    }
+
+    // each boss is a little tougher than the last
+    let bosses_fought = (**level - 1) / BOSS_LEVEL_INTERVAL;
+    let core_position = Vec3::new(0., world_size.1 / 2. - 200., 0.);
+    let velocity = Vec3::new(BOSS_SPEED, 0., 0.);
+    let core_health = BOSS_CORE_HEALTH + bosses_fought.saturating_sub(1) * 5;
+
+    commands.spawn((
+        Boss,
+        BossMaxHealth(core_health + BOSS_SEGMENT_COUNT as u32 * BOSS_SEGMENT_HEALTH),
+        Position(core_position),
+        Velocity(velocity),
+        Size(BOSS_CORE_SIZE),
+        Health::new(core_health),
+        FiringTimer(Timer::from_seconds(BOSS_ATTACK_TIME, TimerMode::Once)),
+        BossAttack::Aimed,
+        Collidable,
+    ));
+
+    for segment in 0..BOSS_SEGMENT_COUNT {
+        let angle = segment as f32 * TAU / BOSS_SEGMENT_COUNT as f32;
+        let offset = Vec2::from_angle(angle).extend(0.) * BOSS_SEGMENT_DISTANCE;
+
+        commands.spawn((
+            BossSegment(offset),
+            Position(core_position + offset),
+            Velocity(velocity),
+            Size(BOSS_SEGMENT_SIZE),
+            Health::new(BOSS_SEGMENT_HEALTH),
+            Collidable,
+        ));
+    }
+}
+
+/// Sweeps the boss from side to side across the top of the screen.
+pub fn steer_boss(
+    world_size: Res<WorldSize>,
+    mut boss_query: Query<(&Position, &mut Velocity), With<Boss>>,
+) {
+    let edge = world_size.0 / 2. - BOSS_SEGMENT_DISTANCE - BOSS_SEGMENT_SIZE;
+
+    for (position, mut velocity) in &mut boss_query {
+        if position.x > edge {
+            velocity.x = -BOSS_SPEED;
+        } else if position.x < -edge {
+            velocity.x = BOSS_SPEED;
+        }
+    }
+}
+
+/// Keeps the segments in place around the core.
+#[allow(clippy::type_complexity)]
+pub fn hold_boss_segments(
+    boss_query: Query<(&Position, &Velocity), With<Boss>>,
+    mut segment_query: Query<(&BossSegment, &mut Position, &mut Velocity), Without<Boss>>,
+) {
+    let Ok((core_position, core_velocity)) = boss_query.get_single() else {
+        return;
+    };
+
+    for (offset, mut position, mut velocity) in &mut segment_query {
+        position.0 = **core_position + **offset;
+        velocity.0 = **core_velocity;
+    }
+}
+
+pub fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boss_query: Query<(&Position, &mut FiringTimer, &mut BossAttack), With<Boss>>,
+    segment_query: Query<&Position, With<BossSegment>>,
+    ship_query: Query<&Position, With<Ship>>,
+) {
+    let Ok((core_position, mut firing_timer, mut attack)) = boss_query.get_single_mut() else {
+        return;
+    };
+
+    if !firing_timer.tick(time.delta()).finished() {
+        return;
+    }
+    // hold fire while the ship is waiting to come back in
+    let Ok(ship_position) = ship_query.get_single() else {
+        return;
+    };
+
+    firing_timer.reset();
+
+    let mut fire = |position: Position, direction: Vec2| {
+        spawn_ufo_bullet(
+            &mut commands,
+            position,
+            direction.extend(0.) * BOSS_BULLET_SPEED,
+            BOSS_BULLET_LIFETIME,
+        );
+    };
+    let aim = |from: &Position| (**ship_position - **from).truncate().normalize_or_zero();
+
+    match *attack {
+        BossAttack::Aimed => {
+            let heading = aim(core_position);
+
+            for bullet in 0..BOSS_FAN_BULLETS {
+                let turn = BOSS_FAN_SPREAD * (bullet as f32 / (BOSS_FAN_BULLETS - 1) as f32 - 0.5);
+
+                fire(*core_position, Vec2::from_angle(turn).rotate(heading));
+            }
+        }
+        BossAttack::Ring => {
+            for bullet in 0..BOSS_RING_BULLETS {
+                let angle = bullet as f32 * TAU / BOSS_RING_BULLETS as f32;
+
+                fire(*core_position, Vec2::from_angle(angle));
+            }
+        }
+        BossAttack::Volley => {
+            for segment_position in &segment_query {
+                fire(*segment_position, aim(segment_position));
+            }
+        }
+    }
+
+    *attack = attack.next();
+}
+
+/// Ship bullets wear down the segments first. The core takes no damage until they are all gone.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn handle_boss_collisions(
+    bullet_query: Query<(&Position, &Size, Entity), (With<Bullet>, With<ShipBullet>)>,
+    mut segment_query: Query<(&Position, &Size, &mut Health, Entity), With<BossSegment>>,
+    mut boss_query: Query<
+        (&Position, &Size, &mut Health, Entity),
+        (With<Boss>, Without<BossSegment>),
+    >,
+    mut commands: Commands,
+    mut explosion_event: EventWriter<ExplosionEvent>,
+    mut score_event: EventWriter<ScoreEvent>,
+    mut hit_event: EventWriter<HitEvent>,
+) {
+    for (bullet_position, bullet_size, bullet_entity) in &bullet_query {
+        let touches = |position: &Position, size: &Size| {
+            bullet_position.distance(**position) <= **bullet_size + **size
+        };
+
+        let segment = segment_query
+            .iter_mut()
+            .find(|(position, size, health, _)| health.current > 0 && touches(position, size));
+
+        if let Some((position, _, mut health, segment_entity)) = segment {
+            commands.entity(bullet_entity).despawn();
+            hit_event.send(HitEvent::Boss);
+            health.current -= 1;
+
+            if health.current == 0 {
+                commands.entity(segment_entity).despawn();
+                explosion_event.send(ExplosionEvent(*position));
+                score_event.send(ScoreEvent(BOSS_SEGMENT_POINTS));
+            }
+
+            continue;
+        }
+
+        let shielded = segment_query
+            .iter()
+            .any(|(_, _, health, _)| health.current > 0);
+
+        for (position, size, mut health, boss_entity) in &mut boss_query {
+            if health.current == 0 || !touches(position, size) {
+                continue;
+            }
+
+            commands.entity(bullet_entity).despawn();
+
+            if shielded {
+                break;
+            }
+
+            hit_event.send(HitEvent::Boss);
+            health.current -= 1;
+
+            if health.current == 0 {
+                commands.entity(boss_entity).despawn();
+                explosion_event.send(ExplosionEvent(*position));
+                score_event.send(ScoreEvent(BOSS_CORE_POINTS));
+            }
+
+            break;
+        }
+    }
+}
+
+/// Back to the asteroids once the core is destroyed.
+pub fn end_boss_fight(
+    boss_query: Query<(), With<Boss>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if boss_query.is_empty() {
+        next_state.set(GameState::GetReady);
+    }
+}
+
+pub fn add_boss_visuals(
+    mut commands: Commands,
+    boss_query: Query<(Entity, &Position, &Size), Added<Boss>>,
+    segment_query: Query<(Entity, &Position, &Size), Added<BossSegment>>,
+) {
+    let core_shape = |size: f32| shapes::RegularPolygon {
+        sides: 6,
+        feature: shapes::RegularPolygonFeature::Radius(size),
+        ..Default::default()
+    };
+    let segment_shape = |size: f32| shapes::RegularPolygon {
+        sides: 4,
+        feature: shapes::RegularPolygonFeature::Radius(size),
+        ..Default::default()
+    };
+    let parts = boss_query
+        .iter()
+        .map(|(entity, position, size)| (entity, position, core_shape(**size), Color::RED))
+        .chain(segment_query.iter().map(|(entity, position, size)| {
+            (entity, position, segment_shape(**size), Color::ORANGE)
+        }));
+
+    for (entity, position, shape, color) in parts {
+        commands.entity(entity).try_insert((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                spatial: SpatialBundle::from_transform(Transform::from_translation(**position)),
+                ..Default::default()
+            },
+            Stroke::new(color, 3.),
+            PreviousPosition(**position),
+        ));
+    }
+}
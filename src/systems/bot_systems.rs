@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use bot::{
+    command::Command,
+    game_info::{wrapped_offset, GameInfo, Hazard, HazardKind},
+};
+
+use crate::{
+    components::{
+        Asteroid, Boss, BossSegment, Firing, Position, RotateSpeed, Rotation, Ship, ShipBullet,
+        Size, Teleport, Thrust, UfoBullet, Velocity, UFO,
+    },
+    events::{HitEvent, ScoreEvent, ShipDestroyedEvent},
+    resources::{BotCommands, BotPilot, WorldSize},
+};
+
+use super::{bullet_systems::BULLET_SPEED, ship_systems::SHIP_ROTATE_SPEED};
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn pilot_ship(
+    mut pilot: ResMut<BotPilot>,
+    mut bot_commands: ResMut<BotCommands>,
+    mut was_teleporting: Local<bool>,
+    world_size: Res<WorldSize>,
+    mut ship_query: Query<
+        (
+            &Position,
+            &Velocity,
+            &Rotation,
+            &mut RotateSpeed,
+            &mut Firing,
+            &mut Thrust,
+            &mut Teleport,
+        ),
+        With<Ship>,
+    >,
+    target_query: Query<
+        (&Position, &Velocity, &Size),
+        Or<(With<Asteroid>, With<UFO>, With<BossSegment>, With<Boss>)>,
+    >,
+    hazard_query: Query<
+        (&Position, &Velocity, &Size, Has<Asteroid>, Has<UfoBullet>),
+        Or<(
+            With<Asteroid>,
+            With<UFO>,
+            With<UfoBullet>,
+            With<BossSegment>,
+            With<Boss>,
+        )>,
+    >,
+    bullet_query: Query<&Position, With<ShipBullet>>,
+) {
+    let Ok((
+        ship_position,
+        ship_velocity,
+        ship_rotation,
+        mut rotate_speed,
+        mut firing,
+        mut thrust,
+        mut teleport,
+    )) = ship_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let arena_size: Vec2 = world_size.as_ref().into();
+
+    rotate_speed.0 = 0.;
+    firing.0 = false;
+    thrust.0 = false;
+
+    let Some((target_position, target_velocity, target_size)) =
+        target_query.iter().min_by(|(a, ..), (b, ..)| {
+            let distance = |position: &Position| {
+                wrapped_offset(ship_position.into(), position.into(), arena_size).length()
+            };
+
+            distance(a).total_cmp(&distance(b))
+        })
+    else {
+        return;
+    };
+
+    let hazards = hazard_query
+        .iter()
+        .map(|(position, velocity, size, is_asteroid, is_bullet)| {
+            // to a bot, the boss is just a big UFO
+            let kind = if is_asteroid {
+                HazardKind::Asteroid
+            } else if is_bullet {
+                HazardKind::UfoBullet
+            } else {
+                HazardKind::Ufo
+            };
+
+            Hazard::new(
+                kind,
+                ship_position.into(),
+                ship_velocity.truncate(),
+                position.into(),
+                velocity.truncate(),
+                **size,
+                arena_size,
+            )
+        });
+
+    let game_info = GameInfo::new(
+        ship_position.into(),
+        arena_size,
+        target_position.into(),
+        target_velocity.truncate(),
+        **target_size,
+        bullet_query.iter().next().map(Vec2::from),
+        BULLET_SPEED,
+        ship_rotation.to_euler(EulerRot::ZYX).0,
+        hazards,
+    );
+
+    pilot.update(game_info);
+
+    let commands = pilot.play(&game_info);
+    bot_commands.0 = commands.to_vec();
+
+    let mut teleporting = false;
+    for command in commands {
+        match command {
+            Command::RotateLeft => rotate_speed.0 = SHIP_ROTATE_SPEED,
+            Command::RotateRight => rotate_speed.0 = -SHIP_ROTATE_SPEED,
+            Command::Fire => firing.0 = true,
+            Command::Thrust => thrust.0 = true,
+            Command::Teleport => teleporting = true,
+            Command::Nothing => (),
+        }
+    }
+
+    // like the hyperspace key, holding teleport only jumps once
+    if teleporting && !*was_teleporting {
+        teleport.0 = true;
+    }
+    *was_teleporting = teleporting;
+}
+
+/// Fills in the pilot's [`bot::fitness::PlayStats`] from what happened this tick.
+pub fn record_play_stats(
+    mut pilot: ResMut<BotPilot>,
+    time: Res<Time>,
+    ship_query: Query<(), With<Ship>>,
+    new_bullet_query: Query<(), Added<ShipBullet>>,
+    mut score_events: EventReader<ScoreEvent>,
+    mut hit_events: EventReader<HitEvent>,
+    mut ship_destroyed_events: EventReader<ShipDestroyedEvent>,
+) {
+    let stats = &mut pilot.stats;
+
+    if !ship_query.is_empty() {
+        stats.survival_time += time.delta_seconds();
+    }
+
+    stats.shots_fired += new_bullet_query.iter().count() as u32;
+
+    for &ScoreEvent(points) in score_events.read() {
+        stats.points += points;
+    }
+
+    for hit in hit_events.read() {
+        stats.hits += 1;
+        if let HitEvent::Asteroid = hit {
+            stats.asteroids_destroyed += 1;
+        }
+    }
+
+    stats.deaths += ship_destroyed_events.read().count() as u32;
+}
@@ -3,15 +3,14 @@ use std::time::Duration;
 use crate::components::*;
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
+pub const BULLET_SPEED: f32 = 1000.;
+
 pub fn ship_fire_bullet(
     mut commands: Commands,
     mut firing_query: Query<&mut Firing>,
     mut ship_query: Query<(&Position, &Rotation, &mut FiringTimer, &Velocity), With<Ship>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
     bullet_query: Query<&Bullet, With<ShipBullet>>,
     time: Res<Time>,
-    asset_server: Res<AssetServer>,
 ) {
     let Ok(mut firing) = firing_query.get_single_mut() else {
         return;
@@ -39,16 +38,8 @@ pub fn ship_fire_bullet(
     }
 
     let bullet_size = Size(7.5);
-    let bullet_position = ship_position.clone();
-    let bullet_mesh = MaterialMesh2dBundle {
-        mesh: meshes.add(Circle::default()).into(),
-        material: materials.add(Color::WHITE),
-        transform: Transform::default()
-            .with_scale(Vec3::splat(*bullet_size))
-            .with_rotation(ship_rotation.0.clone()),
-        ..Default::default()
-    };
-    let mut direction = (bullet_mesh.transform.rotation * Vec3::Y).normalize() * 1000.;
+    let bullet_position = *ship_position;
+    let mut direction = (ship_rotation.0 * Vec3::Y).normalize() * BULLET_SPEED;
 
     direction += ship_velocity.0;
 
@@ -62,19 +53,10 @@ pub fn ship_fire_bullet(
         Bullet,
         bullet_position,
         bullet_velocity,
-        bullet_mesh,
         bullet_timer,
         Collidable,
         bullet_size,
         ShipBullet,
-        AudioBundle {
-            source: asset_server.load("ship_fire.wav"),
-            settings: PlaybackSettings {
-                mode: bevy::audio::PlaybackMode::Remove,
-                ..Default::default()
-            },
-            ..Default::default()
-        },
     ));
 }
 
@@ -101,9 +83,6 @@ pub fn ufo_fire_bullet(
     mut commands: Commands,
     mut ufo_query: Query<(&Position, &mut FiringTimer), With<UFO>>,
     ship_query: Query<&Position, With<Ship>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    asset_server: Res<AssetServer>,
 ) {
     let Some(ship_position) = ship_query.get_single().ok() else {
         return;
@@ -118,38 +97,65 @@ pub fn ufo_fire_bullet(
 
     ufo_firing_timer.reset();
 
-    let bullet_size = Size(7.5);
-    let bullet_position = ufo_position.clone();
-    let bullet_mesh = MaterialMesh2dBundle {
-        mesh: meshes.add(Circle::default()).into(),
-        material: materials.add(Color::ALICE_BLUE),
-        transform: Transform::default().with_scale(Vec3::splat(*bullet_size)),
-        ..Default::default()
-    };
-    let direction = (**ship_position - **ufo_position).normalize() * 1000.;
+    let direction = (**ship_position - **ufo_position).normalize() * BULLET_SPEED;
 
-    let bullet_velocity = Velocity(direction);
-    let bullet_timer = BulletTimer(Timer::new(
+    spawn_ufo_bullet(
+        &mut commands,
+        *ufo_position,
+        direction,
         Duration::from_millis(1000),
-        bevy::time::TimerMode::Once,
-    ));
+    );
+}
 
+/// A bullet that can kill the ship, fired by a UFO or the boss.
+pub fn spawn_ufo_bullet(
+    commands: &mut Commands,
+    position: Position,
+    velocity: Vec3,
+    lifetime: Duration,
+) {
     commands.spawn((
         Bullet,
-        bullet_position,
-        bullet_velocity,
-        bullet_mesh,
-        bullet_timer,
+        position,
+        Velocity(velocity),
+        BulletTimer(Timer::new(lifetime, bevy::time::TimerMode::Once)),
         Collidable,
-        bullet_size,
+        Size(7.5),
         UfoBullet,
-        AudioBundle {
-            source: asset_server.load("ufo_fire.wav"),
-            settings: PlaybackSettings {
-                mode: bevy::audio::PlaybackMode::Remove,
-                ..Default::default()
-            },
-            ..Default::default()
-        },
     ));
 }
+
+pub fn add_bullet_visuals(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    bullet_query: Query<(Entity, &Position, &Size, Has<ShipBullet>), Added<Bullet>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (bullet, position, bullet_size, is_ship_bullet) in &bullet_query {
+        let (color, sound) = if is_ship_bullet {
+            (Color::WHITE, "ship_fire.wav")
+        } else {
+            (Color::ALICE_BLUE, "ufo_fire.wav")
+        };
+        let bullet_mesh = MaterialMesh2dBundle {
+            mesh: meshes.add(Circle::default()).into(),
+            material: materials.add(color),
+            transform: Transform::from_translation(**position)
+                .with_scale(Vec3::splat(**bullet_size)),
+            ..Default::default()
+        };
+
+        commands.entity(bullet).try_insert((
+            bullet_mesh,
+            PreviousPosition(**position),
+            AudioBundle {
+                source: asset_server.load(sound),
+                settings: PlaybackSettings {
+                    mode: bevy::audio::PlaybackMode::Remove,
+                    ..Default::default()
+                },
+            },
+        ));
+    }
+}
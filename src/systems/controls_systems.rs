@@ -0,0 +1,82 @@
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+
+use crate::{
+    bindings::{Action, ActionInput, InputBindings},
+    resources::{BeforePauseState, Rebinding, StorageDirectories},
+    states::{ControlsState, GameState},
+};
+
+use super::shared_systems::save_input_bindings;
+
+pub fn open_controls(
+    mut action_input: ActionInput,
+    mut next_controls_state: ResMut<NextState<ControlsState>>,
+) {
+    if action_input.clear_just_pressed(Action::Controls) {
+        next_controls_state.set(ControlsState::Open);
+    }
+}
+
+/// Binds the next key pressed to the action being rebound, swapping it with any action it clashes
+/// with. The pause key cancels the rebinding instead, and with nothing being rebound it goes back.
+pub fn capture_rebinding(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    storage_directories: Res<StorageDirectories>,
+    mut next_controls_state: ResMut<NextState<ControlsState>>,
+) {
+    let Some(action) = **rebinding else {
+        if bindings.clear_just_pressed(Action::Pause, &mut keyboard_input) {
+            next_controls_state.set(ControlsState::Closed);
+        }
+        return;
+    };
+
+    if bindings.clear_just_pressed(Action::Pause, &mut keyboard_input) {
+        **rebinding = None;
+        return;
+    }
+
+    let Some(&key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+
+    bindings.rebind(action, key);
+    save_input_bindings(&bindings, &storage_directories);
+    **rebinding = None;
+
+    // the key was for rebinding, not for playing
+    keyboard_input.clear();
+}
+
+pub fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    **rebinding = None;
+}
+
+/// Pauses the game when a gamepad goes away mid level, so the ship isn't left flying itself.
+pub fn handle_gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    current_game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut before_pause_state: ResMut<BeforePauseState>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("Gamepad {} connected: {}", event.gamepad.id, info.name);
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad {} disconnected", event.gamepad.id);
+
+                if let GameState::Playing | GameState::Boss = current_game_state.get() {
+                    before_pause_state.0 = *current_game_state.get();
+                    next_game_state.set(GameState::Paused);
+                }
+            }
+        }
+    }
+}
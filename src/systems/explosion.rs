@@ -11,7 +11,7 @@ pub fn handle_explosion_event(
     asset_server: Res<AssetServer>,
 ) {
     for explosion in explosion_event.read() {
-        let position = explosion.0.clone();
+        let position = explosion.0;
         let shape = shapes::Circle {
             radius: 10.,
             center: Vec2::ZERO,
@@ -31,7 +31,6 @@ pub fn handle_explosion_event(
                     mode: bevy::audio::PlaybackMode::Remove,
                     ..Default::default()
                 },
-                ..Default::default()
             },
         ));
     }
@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+use crate::states::GameState;
+
+pub fn skip_title_screen(mut next_game_state: ResMut<NextState<GameState>>) {
+    next_game_state.set(GameState::GetReady);
+}
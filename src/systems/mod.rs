@@ -1,9 +1,15 @@
 pub mod asteroid_systems;
+pub mod boss_systems;
+pub mod bot_systems;
 pub mod bullet_systems;
 pub mod camera_systems;
+pub mod controls_systems;
 pub mod debug_systems;
 pub mod explosion;
+pub mod headless_systems;
+pub mod replay_systems;
 pub mod shared_systems;
 pub mod ship_systems;
+pub mod spectator_systems;
 pub mod ufo_systems;
 pub mod ui;
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{Firing, RotateSpeed, Ship, Teleport, Thrust},
+    replay::{Replay, TickInput},
+    resources::{GameRng, ReplayPlayback, ReplayRecording, Score, StorageDirectories, WorldSize},
+};
+
+/// Runs as a game starts, before anything has been spawned with the randomness.
+pub fn start_recording(
+    mut commands: Commands,
+    rng: Res<GameRng>,
+    world_size: Res<WorldSize>,
+    time: Res<Time<Fixed>>,
+) {
+    let word_position =
+        u64::try_from(rng.word_position()).expect("a game never uses up 2^64 random numbers");
+
+    commands.insert_resource(ReplayRecording(Replay {
+        seed: rng.seed(),
+        word_position,
+        world_size: (world_size.0, world_size.1),
+        timestep: time.timestep(),
+        ..Default::default()
+    }));
+}
+
+/// Puts the randomness back where it was when the recorded game started, so restarting plays
+/// the replay from the beginning again.
+pub fn start_playback(mut playback: ResMut<ReplayPlayback>, mut rng: ResMut<GameRng>) {
+    playback.rewind();
+    *rng = GameRng::resume(playback.replay.seed, playback.replay.word_position.into());
+}
+
+/// Takes the input as the tick is about to use it, however many frames it was held for.
+pub fn record_input(
+    mut recording: ResMut<ReplayRecording>,
+    ship_query: Query<(&RotateSpeed, &Thrust, &Firing, &Teleport), With<Ship>>,
+) {
+    let input = ship_query.get_single().map_or(
+        TickInput::default(),
+        |(rotate_speed, thrust, firing, teleport)| {
+            TickInput::new(**rotate_speed, **thrust, **firing, **teleport)
+        },
+    );
+
+    recording.push(input);
+}
+
+/// Steps through the replay on every tick, including the ones spent waiting for a new ship.
+pub fn play_back_input(
+    mut playback: ResMut<ReplayPlayback>,
+    mut ship_query: Query<(&mut RotateSpeed, &mut Thrust, &mut Firing, &mut Teleport), With<Ship>>,
+) {
+    let input = playback.next_input();
+
+    let Ok((mut rotate_speed, mut thrust, mut firing, mut teleport)) = ship_query.get_single_mut()
+    else {
+        return;
+    };
+
+    rotate_speed.0 = input.rotate_speed();
+    thrust.0 = input.thrust();
+    firing.0 = input.firing();
+    teleport.0 = input.teleport();
+}
+
+/// The recording is done with once it's saved, so nothing else played before the next game starts
+/// recording can end up in it.
+pub fn save_replay(
+    mut commands: Commands,
+    recording: Res<ReplayRecording>,
+    score: Res<Score>,
+    storage_directories: Res<StorageDirectories>,
+) {
+    commands.remove_resource::<ReplayRecording>();
+
+    let Some(path) = storage_directories.replay_path() else {
+        return;
+    };
+
+    if let Some(directory_path) = path.parent() {
+        if let Err(error) = std::fs::create_dir_all(directory_path) {
+            eprintln!("There was an error creating the data directory: {error:?}");
+        }
+    }
+
+    let replay = Replay {
+        score: **score,
+        ..recording.0.clone()
+    };
+
+    match replay.save(&path) {
+        Ok(()) => info!("Saved a replay of the game to {}", path.display()),
+        Err(error) => eprintln!("There was an error saving the replay: {error:?}"),
+    }
+}
+
+pub fn check_replay(playback: Res<ReplayPlayback>, score: Res<Score>) {
+    let replay = &playback.replay;
+
+    if playback.ended_like_recorded(**score) {
+        info!(
+            "The replay ended like the recorded game, {} points after {} ticks",
+            replay.score, replay.ticks
+        );
+    } else {
+        eprintln!(
+            "The replay went differently, {} points after {} ticks instead of {} points after {} ticks",
+            **score, playback.ticks, replay.score, replay.ticks
+        );
+    }
+}
@@ -1,18 +1,82 @@
-use std::ops::Deref;
+use std::{ops::Deref, path::PathBuf};
 
 use crate::{
+    bindings::{Action, ActionInput, InputBindings},
     components::*,
     events::ScoreEvent,
-    resources::{AsteroidCount, BeforeBossState, Countdown, HighScore, Score, WorldSize},
+    resources::{
+        AsteroidCount, BeforeBossState, BeforePauseState, BossKeyUrl, Countdown, GameRng,
+        HighScore, Level, Lives, Score, StorageDirectories, WorldSize,
+    },
     states::GameState,
+    STARTING_LIVES,
 };
 use bevy::prelude::*;
-use directories::ProjectDirs;
 
-pub fn update_positions(mut query: Query<(&mut Transform, &Position)>) {
-    for (mut transform, position) in &mut query {
-        transform.translation.x = position.0.x;
-        transform.translation.y = position.0.y;
+const EXTRA_LIFE_POINTS: u32 = 10_000;
+pub const TITLE_ASTEROID_COUNT: u8 = 10;
+/// Marks a high score file written since asteroids were scored by size. Older files only hold a
+/// number, counted at a point per asteroid.
+const HIGH_SCORE_HEADER: &str = "v2";
+/// Every asteroid is worth at least this much now, so an old high score is never worth more
+/// than it was.
+const LEGACY_POINTS_PER_ASTEROID: u32 = 20;
+
+#[allow(clippy::type_complexity)]
+pub fn store_previous_positions(
+    mut query: Query<(
+        &Position,
+        &mut PreviousPosition,
+        Option<(&Rotation, &mut PreviousRotation)>,
+    )>,
+) {
+    for (position, mut previous_position, rotation) in &mut query {
+        previous_position.0 = position.0;
+
+        if let Some((rotation, mut previous_rotation)) = rotation {
+            previous_rotation.0 = rotation.0;
+        }
+    }
+}
+
+/// Draws entities part way between where they were on the last fixed tick and where they are
+/// now, so movement looks smooth even when frames and ticks don't line up.
+#[allow(clippy::type_complexity)]
+pub fn update_positions(
+    mut query: Query<(
+        &mut Transform,
+        &Position,
+        Option<&PreviousPosition>,
+        Option<&Rotation>,
+        Option<&PreviousRotation>,
+    )>,
+    fixed_time: Res<Time<Fixed>>,
+    world_size: Res<WorldSize>,
+) {
+    let blend = fixed_time.overstep_fraction();
+    let half_world_size = Vec2::from(world_size.deref()) / 2.;
+
+    for (mut transform, position, previous_position, rotation, previous_rotation) in &mut query {
+        let mut translation = position.0;
+
+        if let Some(previous_position) = previous_position {
+            let travelled = position.0 - previous_position.0;
+
+            // wrapping around the edge of the world isn't movement, so don't blend across it
+            if travelled.x.abs() < half_world_size.x && travelled.y.abs() < half_world_size.y {
+                translation = previous_position.lerp(position.0, blend);
+            }
+        }
+
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+
+        if let Some(rotation) = rotation {
+            transform.rotation = match previous_rotation {
+                Some(previous_rotation) => previous_rotation.slerp(rotation.0, blend),
+                None => rotation.0,
+            };
+        }
     }
 }
 
@@ -42,34 +106,51 @@ pub fn wraparound_entities(mut query: Query<&mut Position>, world_size: Res<Worl
 }
 
 pub fn transition_states(
-    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut action_input: ActionInput,
     current_game_state: Res<State<GameState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.clear_just_pressed(KeyCode::Space) {
+    if action_input.clear_just_pressed(Action::Start) {
         match current_game_state.get() {
             GameState::Starting => next_game_state.set(GameState::GetReady),
             GameState::GetReady => unreachable!(),
             GameState::Playing => unreachable!(),
             GameState::GameOver => next_game_state.set(GameState::GetReady),
-            GameState::Boss => todo!(),
+            GameState::Boss => unreachable!(),
+            GameState::BossKey => unreachable!(),
+            GameState::Paused => unreachable!(),
         };
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn reset_game(
     mut asteroid_count: ResMut<AsteroidCount>,
-    mut entities_to_despawn: Query<Entity, Or<(With<Bullet>, With<Asteroid>, With<UFO>)>>,
+    mut level: ResMut<Level>,
+    mut entities_to_despawn: Query<
+        Entity,
+        Or<(
+            With<Bullet>,
+            With<Asteroid>,
+            With<UFO>,
+            With<Boss>,
+            With<BossSegment>,
+            With<Ship>,
+        )>,
+    >,
     mut commands: Commands,
     mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
 ) {
     asteroid_count.0 = 1;
+    level.0 = 1;
 
     for entity in &mut entities_to_despawn {
         commands.entity(entity).despawn();
     }
 
     score.0 = 0;
+    lives.0 = STARTING_LIVES;
 }
 
 pub fn reset_ui(query: Query<Entity, With<UI>>, mut commands: Commands) {
@@ -103,9 +184,10 @@ pub fn transition_from_get_ready_to_playing(
 
 pub fn transition_from_playing_to_game_over(
     ship_query: Query<Entity, With<Ship>>,
+    lives: Res<Lives>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    if !ship_query.is_empty() {
+    if !ship_query.is_empty() || **lives > 0 {
         return;
     }
 
@@ -115,57 +197,207 @@ pub fn transition_from_playing_to_game_over(
 pub fn to_from_boss(
     current_game_state: Res<State<GameState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
-    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut action_input: ActionInput,
     mut before_boss_state: ResMut<BeforeBossState>,
+    boss_key_url: Option<Res<BossKeyUrl>>,
 ) {
-    if keyboard_input.clear_just_pressed(KeyCode::KeyB) {
+    if action_input.clear_just_pressed(Action::BossKey) {
         match current_game_state.get() {
-            GameState::Boss => next_game_state.set(**before_boss_state),
+            GameState::BossKey => next_game_state.set(**before_boss_state),
             GameState::GetReady => (),
             _ => {
                 before_boss_state.0 = *current_game_state.get();
-                next_game_state.set(GameState::Boss);
-                open::that("https://updatefaker.com/").ok();
+                next_game_state.set(GameState::BossKey);
+
+                if let Some(url) = boss_key_url {
+                    if let Err(error) = open::that(&**url) {
+                        eprintln!("There was an error opening {}: {error:?}", **url);
+                    }
+                }
             }
         }
     }
 }
 
-pub fn update_scores(
+/// Stops the game mid level, and starts it again from the pause menu.
+pub fn toggle_pause(
+    mut action_input: ActionInput,
+    current_game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut before_pause_state: ResMut<BeforePauseState>,
+) {
+    // the press is only taken when it pauses or resumes, elsewhere the same key or button can
+    // still start a game, like Start on a gamepad
+    let next_state = match current_game_state.get() {
+        GameState::Paused => **before_pause_state,
+        GameState::Playing | GameState::Boss => GameState::Paused,
+        _ => return,
+    };
+
+    if !action_input.clear_just_pressed(Action::Pause) {
+        return;
+    }
+
+    if next_state == GameState::Paused {
+        before_pause_state.0 = *current_game_state.get();
+    }
+    next_game_state.set(next_state);
+}
+
+/// Quitting to the title brings back its busy field of asteroids.
+pub fn reset_title_asteroids(mut asteroid_count: ResMut<AsteroidCount>) {
+    asteroid_count.0 = TITLE_ASTEROID_COUNT;
+}
+
+/// Runs before [`update_scores`] so it can see which thresholds this tick's points cross.
+pub fn award_extra_lives(
     mut score_events: EventReader<ScoreEvent>,
-    mut score: ResMut<Score>,
-    mut high_score: ResMut<HighScore>,
+    score: Res<Score>,
+    mut lives: ResMut<Lives>,
 ) {
+    let points = score_events
+        .read()
+        .fold(0, |points: u32, &ScoreEvent(value)| {
+            points.saturating_add(value)
+        });
+    let before = **score / EXTRA_LIFE_POINTS;
+    let after = score.saturating_add(points) / EXTRA_LIFE_POINTS;
+
+    **lives = lives.saturating_add((after - before) as u8);
+}
+
+/// Stops everything that runs on virtual time, gameplay included, until [`unpause_time`].
+pub fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+pub fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+pub fn update_scores(mut score_events: EventReader<ScoreEvent>, mut score: ResMut<Score>) {
     for &ScoreEvent(value) in score_events.read() {
-        **score += value;
+        **score = score.saturating_add(value);
+    }
+}
+
+/// Runs after [`update_scores`], and only for the player, so a bot's score is never saved as
+/// the player's high score.
+pub fn update_high_score(score: Res<Score>, mut high_score: ResMut<HighScore>) {
+    if **score > **high_score {
+        **high_score = **score;
+    }
+}
+
+pub fn data_directory() -> Option<PathBuf> {
+    StorageDirectories::default().data
+}
+
+/// Keeps the default bindings when nothing has been rebound yet.
+pub fn load_input_bindings(
+    mut bindings: ResMut<InputBindings>,
+    storage_directories: Res<StorageDirectories>,
+) {
+    let Some(path) = storage_directories
+        .bindings_path()
+        .filter(|path| path.exists())
+    else {
+        return;
+    };
 
-        **high_score = high_score.max(**score);
+    match InputBindings::load(&path) {
+        Ok(loaded) => *bindings = loaded,
+        Err(error) => eprintln!("There was an error loading your controls: {error:?}"),
     }
 }
 
-pub fn save_high_score(high_score: Res<HighScore>) {
-    if let Some(project_directory) = ProjectDirs::from("com", "brooksbuilds", "bevy_asteroids") {
-        let directory_path = project_directory.data_dir();
-        let path = directory_path.join("high_score");
+pub fn save_input_bindings(bindings: &InputBindings, storage_directories: &StorageDirectories) {
+    let Some(path) = storage_directories.bindings_path() else {
+        return;
+    };
 
+    if let Some(directory_path) = path.parent() {
         if let Err(error) = std::fs::create_dir_all(directory_path) {
-            eprintln!("There was an error creating the data directory: {error:?}");
+            eprintln!("There was an error creating the config directory: {error:?}");
+        }
+    }
+
+    if let Err(error) = bindings.save(&path) {
+        eprintln!("There was an error saving your controls: {error:?}");
+    }
+}
+
+pub fn save_high_score(high_score: Res<HighScore>, storage_directories: Res<StorageDirectories>) {
+    if let Some(path) = storage_directories.high_score_path() {
+        if let Some(directory_path) = path.parent() {
+            if let Err(error) = std::fs::create_dir_all(directory_path) {
+                eprintln!("There was an error creating the data directory: {error:?}");
+            }
         }
 
-        if let Err(error) = std::fs::write(path, String::from(*high_score)) {
+        let contents = format!("{HIGH_SCORE_HEADER}\n{}\n", String::from(*high_score));
+
+        if let Err(error) = std::fs::write(path, contents) {
             eprintln!("There was an error saving your high score :( : {error:?}");
         }
     }
 }
 
-pub fn load_high_score(mut high_score: ResMut<HighScore>) {
-    if let Some(project_directory) = ProjectDirs::from("com", "brooksbuilds", "bevy_asteroids") {
-        let directory_path = project_directory.data_dir();
-        let path = directory_path.join("high_score");
-
+pub fn load_high_score(
+    mut high_score: ResMut<HighScore>,
+    storage_directories: Res<StorageDirectories>,
+) {
+    if let Some(path) = storage_directories.high_score_path() {
         match std::fs::read_to_string(path) {
-            Ok(saved_high_score) => **high_score = saved_high_score.parse().unwrap_or_default(),
+            Ok(saved_high_score) => **high_score = parse_high_score(&saved_high_score),
             Err(error) => eprintln!("There was an error saving your high score :( : {error:?}"),
         }
     }
 }
+
+fn parse_high_score(contents: &str) -> u32 {
+    let mut lines = contents.lines().map(str::trim);
+
+    match lines.next() {
+        Some(HIGH_SCORE_HEADER) => lines.next().and_then(|line| line.parse().ok()),
+        Some(legacy) => legacy
+            .parse::<u16>()
+            .ok()
+            .map(|score| u32::from(score).saturating_mul(LEGACY_POINTS_PER_ASTEROID)),
+        None => None,
+    }
+    .unwrap_or_default()
+}
+
+pub fn log_seed(rng: Res<GameRng>) {
+    info!("Playing with seed {}", rng.seed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_high_scores_are_counted_in_points() {
+        assert_eq!(parse_high_score("7\n"), 7 * LEGACY_POINTS_PER_ASTEROID);
+        assert_eq!(
+            parse_high_score(&u16::MAX.to_string()),
+            u32::from(u16::MAX) * LEGACY_POINTS_PER_ASTEROID
+        );
+    }
+
+    #[test]
+    fn high_scores_are_read_after_the_header() {
+        assert_eq!(parse_high_score("v2\n12340\n"), 12340);
+        assert_eq!(parse_high_score(" v2 \n 70000 \n"), 70000);
+    }
+
+    #[test]
+    fn garbage_high_scores_start_over_at_zero() {
+        for contents in [
+            "", "\n", "hello", "-3", "70000", "v2", "v2\nlots", "v3\n100",
+        ] {
+            assert_eq!(parse_high_score(contents), 0, "{contents:?}");
+        }
+    }
+}
@@ -3,57 +3,44 @@ use std::time::Duration;
 use bevy::{core::Zeroable, prelude::*, sprite::MaterialMesh2dBundle};
 
 use crate::{
+    bindings::{Action, ActionInput},
     components::{
-        Asteroid, Collidable, Firing, FiringTimer, Position, RotateSpeed, Rotation, Ship, Size,
-        Thrust, UfoBullet, Velocity,
+        Asteroid, Boss, BossSegment, Collidable, Firing, FiringTimer, Position, PreviousPosition,
+        PreviousRotation, RotateSpeed, Rotation, Ship, Size, Teleport, Thrust, UfoBullet, Velocity,
+        UFO,
     },
-    events::ExplosionEvent,
-    resources::WorldSize,
+    events::{ExplosionEvent, ShipDestroyedEvent},
+    resources::{GameRng, Lives, RespawnTimer, WorldSize},
 };
 
 const NORMAL_SHIP_COLOR_ID: Handle<ColorMaterial> = Handle::weak_from_u128(389743489572398);
 const THRUSTING_SHIP_COLOR_ID: Handle<ColorMaterial> = Handle::weak_from_u128(38475109234891725);
+const SHIP_ACCELERATION: f32 = 60.;
+pub const SHIP_ROTATE_SPEED: f32 = 2.;
+/// How much room a new ship needs around the middle of the screen before it comes in.
+const SAFE_RESPAWN_DISTANCE: f32 = 150.;
 
-pub fn add_player(
-    mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    ship_query: Query<&Ship>,
-    asset_server: Res<AssetServer>,
-) {
+pub fn add_player(mut commands: Commands, ship_query: Query<&Ship>) {
     if !ship_query.is_empty() {
         return;
     }
 
-    materials.insert(NORMAL_SHIP_COLOR_ID, Color::ANTIQUE_WHITE.into());
-    materials.insert(THRUSTING_SHIP_COLOR_ID, Color::RED.into());
+    spawn_ship(&mut commands);
+}
 
+fn spawn_ship(commands: &mut Commands) {
     let ship_size = Size(30.);
-    let ship_mesh = MaterialMesh2dBundle {
-        mesh: meshes.add(Triangle2d::default()).into(),
-        material: NORMAL_SHIP_COLOR_ID,
-        transform: Transform::default().with_scale(Vec3::splat(*ship_size * 2.)),
-        ..Default::default()
-    };
-    let thrust_mesh = MaterialMesh2dBundle {
-        mesh: meshes.add(Rectangle::new(0.3, 0.5)).into(),
-        material: NORMAL_SHIP_COLOR_ID,
-        transform: Transform::default().with_translation(Vec3::new(0., -0.3, -0.1)),
-        ..Default::default()
-    };
     let rotation = Rotation(Quat::default());
     let thrust = Thrust(false);
     let velocity = Velocity(Vec3::zeroed());
-    let ship_thruster = commands.spawn((thrust_mesh,)).id();
     let firing = Firing(false);
     let firing_timer = FiringTimer(Timer::new(
         Duration::from_millis(250),
         bevy::time::TimerMode::Once,
     ));
 
-    let mut ship = commands.spawn((
+    commands.spawn((
         Position(Vec3::zeroed()),
-        ship_mesh,
         RotateSpeed(0.),
         rotation,
         thrust,
@@ -61,19 +48,56 @@ pub fn add_player(
         Ship,
         firing,
         firing_timer,
+        Teleport(false),
         Collidable,
         ship_size,
-        AudioBundle {
-            source: asset_server.load("thrust.wav"),
-            settings: PlaybackSettings {
-                mode: bevy::audio::PlaybackMode::Loop,
-                paused: true,
-                ..Default::default()
-            },
-        },
     ));
+}
 
-    ship.add_child(ship_thruster);
+pub fn add_ship_visuals(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    ship_query: Query<(Entity, &Position, &Rotation, &Size), Added<Ship>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (ship, position, rotation, ship_size) in &ship_query {
+        materials.insert(NORMAL_SHIP_COLOR_ID, Color::ANTIQUE_WHITE.into());
+        materials.insert(THRUSTING_SHIP_COLOR_ID, Color::RED.into());
+
+        let ship_mesh = MaterialMesh2dBundle {
+            mesh: meshes.add(Triangle2d::default()).into(),
+            material: NORMAL_SHIP_COLOR_ID,
+            transform: Transform::from_translation(**position)
+                .with_rotation(**rotation)
+                .with_scale(Vec3::splat(**ship_size * 2.)),
+            ..Default::default()
+        };
+        let thrust_mesh = MaterialMesh2dBundle {
+            mesh: meshes.add(Rectangle::new(0.3, 0.5)).into(),
+            material: NORMAL_SHIP_COLOR_ID,
+            transform: Transform::default().with_translation(Vec3::new(0., -0.3, -0.1)),
+            ..Default::default()
+        };
+        let ship_thruster = commands.spawn((thrust_mesh,)).id();
+
+        commands
+            .entity(ship)
+            .try_insert((
+                ship_mesh,
+                PreviousPosition(**position),
+                PreviousRotation(**rotation),
+                AudioBundle {
+                    source: asset_server.load("thrust.wav"),
+                    settings: PlaybackSettings {
+                        mode: bevy::audio::PlaybackMode::Loop,
+                        paused: true,
+                        ..Default::default()
+                    },
+                },
+            ))
+            .add_child(ship_thruster);
+    }
 }
 
 pub fn change_thruster_colors(
@@ -86,66 +110,58 @@ pub fn change_thruster_colors(
             .expect("couldn't find the first child, which should be thrusters");
 
         if thrust.0 {
-            commands.entity(*thrusters).insert(THRUSTING_SHIP_COLOR_ID);
+            commands
+                .entity(*thrusters)
+                .try_insert(THRUSTING_SHIP_COLOR_ID);
             audio_sink.play();
         } else {
-            commands.entity(*thrusters).insert(NORMAL_SHIP_COLOR_ID);
+            commands.entity(*thrusters).try_insert(NORMAL_SHIP_COLOR_ID);
             audio_sink.pause();
         }
     }
 }
 
-pub fn rotate_ship(
-    time: Res<Time>,
-    mut query: Query<(&mut Transform, &RotateSpeed, &mut Rotation)>,
-) {
-    for (mut transform, rotate_speed, mut rotation) in &mut query {
-        transform.rotate_z(rotate_speed.0 * time.delta_seconds());
-        rotation.0 = transform.rotation;
+/// Audio keeps playing when virtual time stops, so a thrusting ship would carry on roaring.
+pub fn silence_thrusters(query: Query<&AudioSink, With<Ship>>) {
+    for audio_sink in &query {
+        audio_sink.pause();
     }
 }
 
-pub fn input_rotate_ship(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<&mut RotateSpeed>,
-) {
+pub fn rotate_ship(time: Res<Time>, mut query: Query<(&RotateSpeed, &mut Rotation)>) {
+    for (rotate_speed, mut rotation) in &mut query {
+        rotation.0 = Quat::from_rotation_z(rotate_speed.0 * time.delta_seconds()) * rotation.0;
+    }
+}
+
+pub fn input_rotate_ship(action_input: ActionInput, mut query: Query<&mut RotateSpeed>) {
     for mut rotate_speed in &mut query {
-        rotate_speed.0 = if keyboard_input.pressed(KeyCode::ArrowLeft) {
-            2.0
-        } else if keyboard_input.pressed(KeyCode::ArrowRight) {
-            -2.0
-        } else {
-            0.0
-        };
+        rotate_speed.0 = SHIP_ROTATE_SPEED * action_input.rotation();
 
         rotate_speed.0 = rotate_speed.0.clamp(-5., 5.);
     }
 }
 
-pub fn input_thrust_ship(keyboard_input: Res<ButtonInput<KeyCode>>, mut query: Query<&mut Thrust>) {
+pub fn input_thrust_ship(action_input: ActionInput, mut query: Query<&mut Thrust>) {
     for mut thrust in &mut query {
-        thrust.0 = keyboard_input.pressed(KeyCode::ArrowUp);
+        thrust.0 = action_input.pressed(Action::Thrust);
     }
 }
 
-pub fn input_firing(keyboard_input: Res<ButtonInput<KeyCode>>, mut query: Query<&mut Firing>) {
+pub fn input_firing(action_input: ActionInput, mut query: Query<&mut Firing>) {
     let Ok(mut firing) = query.get_single_mut() else {
         return;
     };
 
-    if keyboard_input.pressed(KeyCode::Space) {
-        firing.0 = true;
-    } else {
-        firing.0 = false;
-    }
+    firing.0 = action_input.pressed(Action::Fire);
 }
 
-pub fn apply_thrust(mut query: Query<(&Thrust, &mut Velocity, &Transform)>) {
-    for (thrust, mut velocity, transform) in &mut query {
+pub fn apply_thrust(time: Res<Time>, mut query: Query<(&Thrust, &mut Velocity, &Rotation)>) {
+    for (thrust, mut velocity, rotation) in &mut query {
         if thrust.0 {
-            let acceleration = 1.;
+            let acceleration = SHIP_ACCELERATION * time.delta_seconds();
 
-            let direction = transform.rotation * Vec3::Y;
+            let direction = rotation.0 * Vec3::Y;
             let direction = direction.normalize();
 
             velocity.0 += acceleration * direction;
@@ -153,11 +169,17 @@ pub fn apply_thrust(mut query: Query<(&Thrust, &mut Velocity, &Transform)>) {
     }
 }
 
+/// Flying into an asteroid or any part of the boss.
+#[allow(clippy::type_complexity)]
 pub fn handle_ship_collisions(
-    asteroid_query: Query<(&Position, &Size, Entity), With<Asteroid>>,
+    asteroid_query: Query<
+        (&Position, &Size, Entity),
+        Or<(With<Asteroid>, With<Boss>, With<BossSegment>)>,
+    >,
     ship_query: Query<(&Position, &Size, Entity), With<Ship>>,
     mut bevy_commands: Commands,
     mut explosion_event: EventWriter<ExplosionEvent>,
+    mut ship_destroyed_event: EventWriter<ShipDestroyedEvent>,
 ) {
     for (ship_position, ship_size, ship_entity) in ship_query.iter() {
         for (asteroid_position, asteroid_size, _asteroid_entity) in asteroid_query.iter() {
@@ -166,20 +188,30 @@ pub fn handle_ship_collisions(
             }
 
             bevy_commands.entity(ship_entity).despawn_recursive();
-            explosion_event.send(ExplosionEvent(ship_position.clone()));
+            explosion_event.send(ExplosionEvent(*ship_position));
+            ship_destroyed_event.send(ShipDestroyedEvent);
             break;
         }
     }
 }
 
+pub fn input_teleport_ship(mut action_input: ActionInput, mut query: Query<&mut Teleport>) {
+    if action_input.clear_just_pressed(Action::Hyperspace) {
+        for mut teleport in &mut query {
+            teleport.0 = true;
+        }
+    }
+}
+
 pub fn teleport_ship(
-    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
     world_size: Res<WorldSize>,
-    mut ship_query: Query<&mut Position, With<Ship>>,
+    mut ship_query: Query<(&mut Position, &mut Teleport), With<Ship>>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if keyboard_input.clear_just_pressed(KeyCode::Enter) {
-        if let Some(mut ship_position) = ship_query.get_single_mut().ok() {
-            ship_position.set_random(&*world_size);
+    for (mut ship_position, mut teleport) in &mut ship_query {
+        if teleport.0 {
+            ship_position.set_random(&world_size, &mut **rng);
+            teleport.0 = false;
         }
     }
 }
@@ -188,6 +220,7 @@ pub fn handle_ship_bullet_collisions(
     bullet_query: Query<(&Position, &Size, Entity), With<UfoBullet>>,
     mut commands: Commands,
     mut explosion_event: EventWriter<ExplosionEvent>,
+    mut ship_destroyed_event: EventWriter<ShipDestroyedEvent>,
 ) {
     for (bullet_position, bullet_size, bullet_entity) in bullet_query.iter() {
         for (ship_position, ship_size, ship) in ship_query.iter() {
@@ -197,9 +230,60 @@ pub fn handle_ship_bullet_collisions(
             commands.entity(ship).despawn_recursive();
             commands.entity(bullet_entity).despawn();
 
-            explosion_event.send(ExplosionEvent(ship_position.clone()));
+            explosion_event.send(ExplosionEvent(*ship_position));
+            ship_destroyed_event.send(ShipDestroyedEvent);
 
             break; // Each bullet can only hit one asteroid
         }
     }
 }
+
+pub fn lose_life(
+    mut ship_destroyed_event: EventReader<ShipDestroyedEvent>,
+    mut lives: ResMut<Lives>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+) {
+    // a ship can be hit by more than one thing on the same tick
+    if ship_destroyed_event.read().count() == 0 {
+        return;
+    }
+
+    **lives = lives.saturating_sub(1);
+    respawn_timer.reset();
+}
+
+/// Brings in the next ship once the timer is up and nothing is about to fly into it.
+#[allow(clippy::type_complexity)]
+pub fn respawn_ship(
+    mut commands: Commands,
+    time: Res<Time>,
+    lives: Res<Lives>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+    ship_query: Query<&Ship>,
+    hazard_query: Query<
+        (&Position, &Size),
+        Or<(
+            With<Asteroid>,
+            With<UFO>,
+            With<UfoBullet>,
+            With<Boss>,
+            With<BossSegment>,
+        )>,
+    >,
+) {
+    if !ship_query.is_empty() || **lives == 0 {
+        return;
+    }
+
+    if !respawn_timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    let blocked = hazard_query
+        .iter()
+        .any(|(position, size)| position.length() < SAFE_RESPAWN_DISTANCE + **size);
+
+    if !blocked {
+        spawn_ship(&mut commands);
+    }
+}
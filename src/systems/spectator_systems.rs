@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bot::{fitness::PlayStats, individual::Individual};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bindings::{Action, ActionInput},
+    resources::{BotPilot, GameRng, ReplayRecording, SelectedIndividual, TrainingFeed},
+    states::{GameState, SpectatorState},
+};
+
+/// `None` until the training run has scored its first generation.
+fn selected_individual(feed: &TrainingFeed, selected: usize) -> Option<Individual> {
+    let snapshot = feed.lock().unwrap();
+
+    match selected {
+        0 => snapshot.champion.clone(),
+        place => snapshot.population.get(place - 1).cloned(),
+    }
+}
+
+/// Hands the ship to the selected individual at the start of every game, so a new champion
+/// takes over as soon as there is one.
+pub fn fly_selected(
+    mut commands: Commands,
+    feed: Res<TrainingFeed>,
+    selected: Res<SelectedIndividual>,
+    pilot: Option<Res<BotPilot>>,
+    rng: Res<GameRng>,
+) {
+    // seeded apart from the game's own randomness, so the untrained bot doesn't change the game
+    let mut individual = selected_individual(&feed, **selected)
+        .or_else(|| pilot.map(|pilot| pilot.0.clone()))
+        .unwrap_or_else(|| Individual::new(&mut ChaCha8Rng::seed_from_u64(rng.seed())));
+    individual.stats = PlayStats::default();
+
+    // a bot's input would end up in the player's replay
+    commands.remove_resource::<ReplayRecording>();
+    commands.insert_resource(BotPilot(individual));
+}
+
+pub fn stop_flying(mut commands: Commands) {
+    commands.remove_resource::<BotPilot>();
+}
+
+/// Swaps in the next individual straight away, mid game.
+pub fn cycle_individuals(
+    action_input: ActionInput,
+    feed: Res<TrainingFeed>,
+    mut selected: ResMut<SelectedIndividual>,
+    mut pilot: ResMut<BotPilot>,
+) {
+    if !action_input.just_pressed(Action::NextIndividual) {
+        return;
+    }
+
+    let choices = feed.lock().unwrap().population.len() + 1;
+    **selected = (**selected + 1) % choices;
+
+    if let Some(individual) = selected_individual(&feed, **selected) {
+        let stats = pilot.stats;
+
+        pilot.0 = individual;
+        pilot.stats = stats;
+    }
+}
+
+pub fn toggle_watching(
+    action_input: ActionInput,
+    current_spectator_state: Res<State<SpectatorState>>,
+    mut next_spectator_state: ResMut<NextState<SpectatorState>>,
+) {
+    if !action_input.just_pressed(Action::ToggleWatching) {
+        return;
+    }
+
+    match current_spectator_state.get() {
+        SpectatorState::Off => next_spectator_state.set(SpectatorState::Watching),
+        SpectatorState::Watching => next_spectator_state.set(SpectatorState::Off),
+    }
+}
+
+/// Keeps games going without anyone pressing space.
+pub fn start_next_game(mut next_game_state: ResMut<NextState<GameState>>) {
+    next_game_state.set(GameState::GetReady);
+}
@@ -1,11 +1,16 @@
 use crate::{
-    components::{Bullet, Collidable, FiringTimer, Position, ShipBullet, Size, Velocity, UFO},
-    events::{ExplosionEvent, ScoreEvent},
-    resources::{UfoTimer, WorldSize},
+    components::{
+        Bullet, Collidable, FiringTimer, Position, PreviousPosition, ShipBullet, Size, UfoKind,
+        Velocity, UFO,
+    },
+    events::{ExplosionEvent, HitEvent, ScoreEvent},
+    resources::{GameRng, UfoTimer, WorldSize},
 };
 use bevy::prelude::*;
 use bevy_prototype_lyon::{draw::Stroke, entity::ShapeBundle, geometry::GeometryBuilder, shapes};
-use rand::{thread_rng, Rng};
+use rand::Rng;
+
+const SMALL_UFO_CHANCE: f64 = 0.25;
 
 pub fn set_ufo_spawn_timer(mut ufo_spawn_timer: ResMut<UfoTimer>) {
     ufo_spawn_timer.reset()
@@ -19,29 +24,48 @@ pub fn spawn_ufo(
     ufo_spawn_timer: Res<UfoTimer>,
     mut commands: Commands,
     world_size: Res<WorldSize>,
+    mut rng: ResMut<GameRng>,
 ) {
     if !ufo_spawn_timer.just_finished() {
         return;
     }
 
-    let size = Size(50.);
-    let shape = create_ufo_shape(*size);
+    let kind = if rng.gen_bool(SMALL_UFO_CHANCE) {
+        UfoKind::Small
+    } else {
+        UfoKind::Large
+    };
 
     commands.spawn((
-        ShapeBundle {
-            path: GeometryBuilder::build_as(&shape),
-            ..Default::default()
-        },
-        Stroke::new(Color::ANTIQUE_WHITE, 2.),
         UFO,
-        Position::new_random_edge(&*world_size),
-        size,
+        kind,
+        Position::new_random_edge(&world_size, &mut **rng),
+        kind.size(),
         Velocity(Vec3::ZERO),
         Collidable,
         FiringTimer(Timer::from_seconds(0.5, TimerMode::Once)),
     ));
 }
 
+pub fn add_ufo_visuals(
+    mut commands: Commands,
+    ufo_query: Query<(Entity, &Position, &Size), Added<UFO>>,
+) {
+    for (ufo, position, size) in &ufo_query {
+        let shape = create_ufo_shape(**size);
+
+        commands.entity(ufo).try_insert((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                spatial: SpatialBundle::from_transform(Transform::from_translation(**position)),
+                ..Default::default()
+            },
+            Stroke::new(Color::ANTIQUE_WHITE, 2.),
+            PreviousPosition(**position),
+        ));
+    }
+}
+
 fn create_ufo_shape(size: f32) -> bevy_prototype_lyon::shapes::Polygon {
     let size = Vec2::new(size, size * 0.85);
     let half_size = size * 0.5;
@@ -62,8 +86,7 @@ fn create_ufo_shape(size: f32) -> bevy_prototype_lyon::shapes::Polygon {
     }
 }
 
-pub fn update_velocity(mut ufo_query: Query<&mut Velocity, With<UFO>>) {
-    let mut rng = thread_rng();
+pub fn update_velocity(mut ufo_query: Query<&mut Velocity, With<UFO>>, mut rng: ResMut<GameRng>) {
     let force = Vec3::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0), 0.);
 
     for mut velocity in &mut ufo_query {
@@ -72,22 +95,24 @@ pub fn update_velocity(mut ufo_query: Query<&mut Velocity, With<UFO>>) {
 }
 
 pub fn handle_ufo_bullet_collisions(
-    ufo_query: Query<(&Position, &Size, Entity), With<UFO>>,
+    ufo_query: Query<(&Position, &Size, &UfoKind, Entity), With<UFO>>,
     bullet_query: Query<(&Position, &Size, Entity, &ShipBullet), With<Bullet>>,
     mut commands: Commands,
     mut explosion_event: EventWriter<ExplosionEvent>,
     mut score_event: EventWriter<ScoreEvent>,
+    mut hit_event: EventWriter<HitEvent>,
 ) {
     for (bullet_position, bullet_size, bullet_entity, _ship_marker) in bullet_query.iter() {
-        for (ufo_position, ufo_size, ufo) in ufo_query.iter() {
+        for (ufo_position, ufo_size, ufo_kind, ufo) in ufo_query.iter() {
             if bullet_position.distance(**ufo_position) > **bullet_size + **ufo_size {
                 continue;
             }
             commands.entity(ufo).despawn();
             commands.entity(bullet_entity).despawn();
 
-            explosion_event.send(ExplosionEvent(ufo_position.clone()));
-            score_event.send(ScoreEvent(10));
+            explosion_event.send(ExplosionEvent(*ufo_position));
+            score_event.send(ScoreEvent(ufo_kind.points()));
+            hit_event.send(HitEvent::Ufo);
 
             break; // Each bullet can only hit one asteroid
         }
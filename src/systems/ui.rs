@@ -1,21 +1,65 @@
 use bevy::{
+    asset::{Assets, Handle},
     ecs::{
+        change_detection::{DetectChanges, Ref},
+        component::Component,
+        entity::Entity,
         query::With,
-        system::{Commands, Query, Res},
+        schedule::NextState,
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    render::{
+        color::Color,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::Image,
     },
     text::{Text, TextSection, TextStyle},
-    ui::{node_bundles::TextBundle, Style, UiRect},
+    ui::{
+        node_bundles::{ButtonBundle, ImageBundle, NodeBundle, TextBundle},
+        AlignItems, BackgroundColor, BorderColor, Display, FlexDirection, GridTrack, Interaction,
+        JustifyContent, JustifySelf, PositionType, RepeatedGridTrack, Style, UiImage, UiRect, Val,
+        ZIndex,
+    },
 };
+use bot::command::Command;
 
 use crate::{
-    components::{ScoreUI, UI},
-    resources::{Countdown, HighScore, Score},
+    bindings::{Action, ActionInput, InputBindings},
+    components::{
+        BindingText, Boss, BossHealthBar, BossHealthUI, BossMaxHealth, BossScreenUI,
+        ControlsButton, ControlsUI, Health, LivesUI, PauseMenuButton, PauseMenuUI, ScoreUI,
+        SpectatorUI, UI,
+    },
+    resources::{
+        BeforePauseState, BotCommands, Countdown, HighScore, Lives, MenuFocus, Rebinding, Score,
+        SelectedIndividual, TrainingFeed,
+    },
+    states::{ControlsState, GameState},
     GET_READY_TIME,
 };
 
-pub fn title_screen(mut commands: Commands) {
+const SPREADSHEET_COLUMNS: u16 = 8;
+const SPREADSHEET_ROWS: u16 = 30;
+const SPREADSHEET_HEADINGS: [&str; SPREADSHEET_COLUMNS as usize] = [
+    "Region", "Q1", "Q2", "Q3", "Q4", "Total", "Target", "Variance",
+];
+const SPREADSHEET_REGIONS: [&str; 6] = ["North", "South", "East", "West", "Central", "Online"];
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+const LIFE_ICON_ID: Handle<Image> = Handle::weak_from_u128(90823475092384751);
+const LIFE_ICON_SIZE: u32 = 20;
+
+pub fn title_screen(mut commands: Commands, bindings: Res<InputBindings>) {
     let game_name = "Asteroids";
-    let start = "\nPress space to start";
+    let start = format!(
+        "\nPress {} to start\nPress {} for controls",
+        bindings.describe(Action::Start),
+        bindings.describe(Action::Controls)
+    );
 
     commands.spawn((
         TextBundle::from_sections([
@@ -99,9 +143,13 @@ pub fn update_score_ui(
     text.sections[3].value = String::from(*high_score);
 }
 
-pub fn game_over_screen(mut commands: Commands, score: Res<Score>) {
+pub fn game_over_screen(mut commands: Commands, score: Res<Score>, bindings: Res<InputBindings>) {
     let title = "Game Over";
-    let subtitle = format!("\nScore: {}\nPress space to try again", **score);
+    let subtitle = format!(
+        "\nScore: {}\nPress {} to try again",
+        **score,
+        bindings.describe(Action::Start)
+    );
 
     commands.spawn((
         TextBundle::from_sections([
@@ -171,3 +219,654 @@ pub fn display_score(mut commands: Commands, score: Res<Score>, high_score: Res<
         ScoreUI,
     ));
 }
+
+/// A row of ship icons under the score, one for each life left.
+pub fn display_lives(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    images.insert(LIFE_ICON_ID, life_icon());
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                justify_self: JustifySelf::End,
+                top: Val::Px(32.),
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(6.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        LivesUI,
+    ));
+}
+
+/// An upward pointing triangle, drawn the colour of the ship.
+fn life_icon() -> Image {
+    let size = LIFE_ICON_SIZE as f32;
+    let data = (0..LIFE_ICON_SIZE)
+        .flat_map(|y| (0..LIFE_ICON_SIZE).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let half_width = (y as f32 + 0.5) / 2.;
+            let inside = (x as f32 + 0.5 - size / 2.).abs() <= half_width;
+
+            if inside {
+                [250, 235, 215, 255]
+            } else {
+                [0, 0, 0, 0]
+            }
+        })
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width: LIFE_ICON_SIZE,
+            height: LIFE_ICON_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+pub fn update_lives_ui(
+    mut commands: Commands,
+    query: Query<Entity, With<LivesUI>>,
+    lives: Res<Lives>,
+) {
+    let Ok(lives_ui) = query.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(lives_ui)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for _ in 0..**lives {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(LIFE_ICON_SIZE as f32),
+                        height: Val::Px(LIFE_ICON_SIZE as f32),
+                        ..Default::default()
+                    },
+                    image: UiImage::new(LIFE_ICON_ID),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+pub fn display_boss_health(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    justify_self: JustifySelf::Center,
+                    top: Val::Px(40.),
+                    width: Val::Percent(40.),
+                    height: Val::Px(16.),
+                    border: UiRect::all(Val::Px(2.)),
+                    ..Default::default()
+                },
+                border_color: BorderColor(Color::ANTIQUE_WHITE),
+                ..Default::default()
+            },
+            BossHealthUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::RED),
+                    ..Default::default()
+                },
+                BossHealthBar,
+            ));
+        });
+}
+
+/// Shrinks the bar with what's left of the core and its segments together.
+pub fn update_boss_health(
+    mut bar_query: Query<&mut Style, With<BossHealthBar>>,
+    boss_query: Query<&BossMaxHealth, With<Boss>>,
+    health_query: Query<&Health>,
+) {
+    let Ok(mut style) = bar_query.get_single_mut() else {
+        return;
+    };
+
+    let max = boss_query.get_single().map_or(1, |max| **max);
+    let current: u32 = health_query.iter().map(|health| health.current).sum();
+
+    style.width = Val::Percent(100. * current as f32 / max.max(1) as f32);
+}
+
+pub fn remove_boss_health(mut commands: Commands, query: Query<Entity, With<BossHealthUI>>) {
+    for health_ui in &query {
+        commands.entity(health_ui).despawn_recursive();
+    }
+}
+
+pub fn display_pause_menu(mut commands: Commands) {
+    commands.insert_resource(MenuFocus::<PauseMenuButton>::default());
+
+    let buttons = [
+        (PauseMenuButton::Resume, "Resume"),
+        (PauseMenuButton::Restart, "Restart"),
+        (PauseMenuButton::Controls, "Controls"),
+        (PauseMenuButton::Quit, "Quit to title"),
+    ];
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(15.),
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.6)),
+                ..Default::default()
+            },
+            PauseMenuUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 100.,
+                    ..Default::default()
+                },
+            ));
+
+            for (button, label) in buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.),
+                                padding: UiRect::all(Val::Px(10.)),
+                                border: UiRect::all(Val::Px(2.)),
+                                justify_content: JustifyContent::Center,
+                                ..Default::default()
+                            },
+                            border_color: BorderColor(Color::ANTIQUE_WHITE),
+                            background_color: BackgroundColor(BUTTON_COLOR),
+                            ..Default::default()
+                        },
+                        button,
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font_size: 40.,
+                                ..Default::default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+pub fn pause_menu_buttons(
+    mut button_query: Query<(Ref<Interaction>, &PauseMenuButton, &mut BackgroundColor)>,
+    mut action_input: ActionInput,
+    focus: Res<MenuFocus<PauseMenuButton>>,
+    before_pause_state: Res<BeforePauseState>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_controls_state: ResMut<NextState<ControlsState>>,
+) {
+    let selected = action_input.clear_just_pressed(Action::MenuSelect);
+
+    match pressed_button(&mut button_query, **focus, selected) {
+        Some(PauseMenuButton::Resume) => next_game_state.set(**before_pause_state),
+        Some(PauseMenuButton::Restart) => next_game_state.set(GameState::GetReady),
+        Some(PauseMenuButton::Controls) => next_controls_state.set(ControlsState::Open),
+        Some(PauseMenuButton::Quit) => next_game_state.set(GameState::Starting),
+        None => (),
+    }
+}
+
+/// Highlights the button under the mouse or moved to with the menu keys, and returns the one
+/// clicked this frame or moved to and `selected`.
+fn pressed_button<T: Component + Copy + PartialEq>(
+    button_query: &mut Query<(Ref<Interaction>, &T, &mut BackgroundColor)>,
+    focus: Option<T>,
+    selected: bool,
+) -> Option<T> {
+    let mut pressed = None;
+
+    for (interaction, &button, mut background_color) in button_query {
+        let focused = focus == Some(button);
+        let color = if focused || *interaction != Interaction::None {
+            HOVERED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+
+        if (interaction.is_changed() && *interaction == Interaction::Pressed)
+            || (focused && selected)
+        {
+            pressed = Some(button);
+        }
+    }
+
+    pressed
+}
+
+/// Moves between a menu's buttons in the order they're shown, wrapping around at either end.
+pub fn move_menu_focus<T: Component + Copy + Ord>(
+    mut action_input: ActionInput,
+    button_query: Query<&T>,
+    mut focus: ResMut<MenuFocus<T>>,
+) {
+    let down = action_input.clear_just_pressed(Action::MenuDown);
+    let up = action_input.clear_just_pressed(Action::MenuUp);
+
+    if down == up {
+        return;
+    }
+
+    let mut buttons: Vec<T> = button_query.iter().copied().collect();
+    buttons.sort_unstable();
+
+    let count = buttons.len();
+    let index = match focus.and_then(|focus| buttons.iter().position(|&button| button == focus)) {
+        Some(index) if down => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        // the first press lands on the first or the last button
+        None if down => 0,
+        None => count.wrapping_sub(1),
+    };
+
+    **focus = buttons.get(index).copied();
+}
+
+pub fn remove_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuUI>>) {
+    for pause_menu in &query {
+        commands.entity(pause_menu).despawn_recursive();
+    }
+}
+
+pub fn display_controls_screen(mut commands: Commands, bindings: Res<InputBindings>) {
+    commands.insert_resource(MenuFocus::<ControlsButton>::default());
+
+    let button_style = Style {
+        width: Val::Px(500.),
+        padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+        border: UiRect::all(Val::Px(2.)),
+        ..Default::default()
+    };
+    let label = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                ..Default::default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.),
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.9)),
+                z_index: ZIndex::Global(1),
+                ..Default::default()
+            },
+            ControlsUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(label("Controls".to_string(), 60.));
+
+            for action in Action::ALL {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: button_style.clone(),
+                            border_color: BorderColor(Color::ANTIQUE_WHITE),
+                            background_color: BackgroundColor(BUTTON_COLOR),
+                            ..Default::default()
+                        },
+                        ControlsButton::Rebind(action),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((label(String::new(), 25.), BindingText(action)));
+                    });
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            justify_content: JustifyContent::Center,
+                            ..button_style.clone()
+                        },
+                        border_color: BorderColor(Color::ANTIQUE_WHITE),
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..Default::default()
+                    },
+                    ControlsButton::Back,
+                ))
+                .with_children(|button| {
+                    button.spawn(label("Back".to_string(), 25.));
+                });
+
+            parent.spawn(label(
+                format!(
+                    "Click or select an action, then press its new key. {} to go back.",
+                    bindings.describe(Action::Pause)
+                ),
+                20.,
+            ));
+        });
+}
+
+pub fn update_controls_screen(
+    mut query: Query<(&mut Text, &BindingText)>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+) {
+    for (mut text, action) in &mut query {
+        let keys = if **rebinding == Some(**action) {
+            "press a key...".to_string()
+        } else {
+            bindings.describe(**action)
+        };
+
+        text.sections[0].value = format!("{}: {keys}", action.name());
+    }
+}
+
+/// Clicking an action waits for its new key, clicking it again stops waiting.
+pub fn controls_screen_buttons(
+    mut button_query: Query<(Ref<Interaction>, &ControlsButton, &mut BackgroundColor)>,
+    mut action_input: ActionInput,
+    focus: Res<MenuFocus<ControlsButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_controls_state: ResMut<NextState<ControlsState>>,
+) {
+    let selected = action_input.clear_just_pressed(Action::MenuSelect);
+
+    match pressed_button(&mut button_query, **focus, selected) {
+        Some(ControlsButton::Rebind(action)) if **rebinding == Some(action) => **rebinding = None,
+        Some(ControlsButton::Rebind(action)) => **rebinding = Some(action),
+        Some(ControlsButton::Back) => next_controls_state.set(ControlsState::Closed),
+        None => (),
+    }
+}
+
+pub fn remove_controls_screen(mut commands: Commands, query: Query<Entity, With<ControlsUI>>) {
+    for controls in &query {
+        commands.entity(controls).despawn_recursive();
+    }
+}
+
+/// A dull looking spreadsheet over the whole window, with nothing of the game showing through.
+pub fn display_boss_screen(mut commands: Commands) {
+    let text = |value: String, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: 16.,
+                color,
+                ..Default::default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(Color::WHITE),
+                z_index: ZIndex::Global(i32::MAX),
+                ..Default::default()
+            },
+            BossScreenUI,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(6.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::rgb(0.13, 0.45, 0.27)),
+                    ..Default::default()
+                })
+                .with_children(|title_bar| {
+                    title_bar.spawn(text(
+                        "Regional forecast FINAL v3 (2).xlsx".to_string(),
+                        Color::WHITE,
+                    ));
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::flex(SPREADSHEET_COLUMNS, 1.),
+                        grid_auto_rows: vec![GridTrack::px(22.)],
+                        column_gap: Val::Px(1.),
+                        row_gap: Val::Px(1.),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::GRAY),
+                    ..Default::default()
+                })
+                .with_children(|grid| {
+                    for row in 0..SPREADSHEET_ROWS {
+                        for column in 0..SPREADSHEET_COLUMNS {
+                            let (value, background) = spreadsheet_cell(row, column);
+
+                            grid.spawn(NodeBundle {
+                                style: Style {
+                                    padding: UiRect::horizontal(Val::Px(4.)),
+                                    ..Default::default()
+                                },
+                                background_color: BackgroundColor(background),
+                                ..Default::default()
+                            })
+                            .with_children(|cell| {
+                                cell.spawn(text(value, Color::BLACK));
+                            });
+                        }
+                    }
+                });
+        });
+}
+
+/// Made up numbers that look like they add up. They don't come from the game's rng, so hiding
+/// the game doesn't change how it plays.
+fn spreadsheet_cell(row: u16, column: u16) -> (String, Color) {
+    let heading_color = Color::rgb(0.9, 0.9, 0.9);
+
+    if row == 0 {
+        return (
+            SPREADSHEET_HEADINGS[column as usize].to_string(),
+            heading_color,
+        );
+    }
+
+    let quarter = |quarter: u32| 1200 + (u32::from(row) * 7919 + quarter * 104_729) % 8800;
+    let total: u32 = (1..=4).map(quarter).sum();
+    let target = 20_000 + u32::from(row) * 375;
+
+    let value = match column {
+        0 => format!(
+            "{} {}",
+            SPREADSHEET_REGIONS[row as usize % SPREADSHEET_REGIONS.len()],
+            row
+        ),
+        1..=4 => quarter(u32::from(column)).to_string(),
+        5 => total.to_string(),
+        6 => target.to_string(),
+        _ => (i64::from(total) - i64::from(target)).to_string(),
+    };
+    let background = if column == 0 {
+        heading_color
+    } else {
+        Color::WHITE
+    };
+
+    (value, background)
+}
+
+pub fn remove_boss_screen(mut commands: Commands, query: Query<Entity, With<BossScreenUI>>) {
+    for boss_screen in &query {
+        commands.entity(boss_screen).despawn_recursive();
+    }
+}
+
+pub fn display_spectator_overlay(mut commands: Commands) {
+    let section = |value: &str| {
+        TextSection::new(
+            value,
+            TextStyle {
+                font_size: 20.,
+                ..Default::default()
+            },
+        )
+    };
+
+    commands.spawn((
+        TextBundle::from_sections([
+            section("Generation: "),
+            section(""),
+            section("\nChampion fitness: "),
+            section(""),
+            section("\nWatching: "),
+            section(""),
+            section("\nCommands: "),
+            section(""),
+            section("\nTab: next individual, V: take over"),
+        ])
+        .with_style(Style {
+            position_type: bevy::ui::PositionType::Absolute,
+            justify_self: bevy::ui::JustifySelf::Start,
+            ..Default::default()
+        }),
+        SpectatorUI,
+    ));
+}
+
+pub fn update_spectator_overlay(
+    mut query: Query<&mut Text, With<SpectatorUI>>,
+    feed: Res<TrainingFeed>,
+    selected: Res<SelectedIndividual>,
+    bot_commands: Res<BotCommands>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    let snapshot = feed.lock().unwrap();
+
+    let Some(champion) = &snapshot.champion else {
+        text.sections[1].value = String::from("training the first one");
+        return;
+    };
+
+    let watching = match **selected {
+        0 => String::from("champion"),
+        place => {
+            let fitness = snapshot
+                .population
+                .get(place - 1)
+                .map_or(0., |individual| individual.score);
+
+            format!("#{place} of {} ({fitness:.2})", snapshot.population.len())
+        }
+    };
+    let commands: Vec<String> = bot_commands
+        .iter()
+        .filter(|&&command| command != Command::Nothing)
+        .map(|command| format!("{command:?}"))
+        .collect();
+
+    text.sections[1].value = snapshot.generation.to_string();
+    text.sections[3].value = format!("{:.2}", champion.score);
+    text.sections[5].value = watching;
+    text.sections[7].value = if commands.is_empty() {
+        String::from("Nothing")
+    } else {
+        commands.join(", ")
+    };
+}
+
+pub fn remove_spectator_overlay(query: Query<Entity, With<SpectatorUI>>, mut commands: Commands) {
+    for overlay in &query {
+        commands.entity(overlay).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        math::Vec3,
+    };
+
+    use super::*;
+    use crate::components::BossSegment;
+
+    fn bar_width(app: &mut App) -> Val {
+        app.world
+            .query_filtered::<&Style, With<BossHealthBar>>()
+            .single(&app.world)
+            .width
+    }
+
+    #[test]
+    fn the_boss_health_bar_goes_down_as_segments_are_destroyed() {
+        let mut app = App::new();
+        app.add_systems(Update, update_boss_health);
+
+        app.world.spawn((Style::default(), BossHealthBar));
+        app.world.spawn((Boss, BossMaxHealth(20), Health::new(10)));
+        let segment = app
+            .world
+            .spawn((BossSegment(Vec3::ZERO), Health::new(5)))
+            .id();
+        app.world.spawn((BossSegment(Vec3::ZERO), Health::new(5)));
+
+        app.update();
+        assert_eq!(bar_width(&mut app), Val::Percent(100.));
+
+        app.world.despawn(segment);
+        app.update();
+        assert_eq!(bar_width(&mut app), Val::Percent(75.));
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use bot::{
+    config::BotConfig, crossover::CrossoverOperator, fitness::PlayStats, individual::Individual,
+    metrics::MetricsLog, Bot, SelectionStrategy,
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    options::{CrossoverArg, Options, Selection},
+    resources::{BotPilot, GameRng},
+    simulation::SimulationPlugin,
+    states::GameState,
+    systems,
+};
+
+const ROUNDS_PER_INDIVIDUAL: u64 = 3;
+const DEFAULT_TOURNAMENT_SIZE: usize = 3;
+const MAX_ROUND_SECONDS: f64 = 120.;
+const CHECKPOINT_FILE: &str = "checkpoint.ron";
+const CHAMPION_FILE: &str = "champion.ron";
+const METRICS_FILE: &str = "metrics.csv";
+const METRICS_JSON_LINES_FILE: &str = "metrics.jsonl";
+
+/// Where trained bots are kept, next to the high score.
+pub fn bot_directory() -> Option<PathBuf> {
+    systems::shared_systems::data_directory().map(|directory| directory.join("bots"))
+}
+
+pub fn champion_path() -> Option<PathBuf> {
+    bot_directory().map(|directory| directory.join(CHAMPION_FILE))
+}
+
+pub fn checkpoint_path() -> Option<PathBuf> {
+    bot_directory().map(|directory| directory.join(CHECKPOINT_FILE))
+}
+
+/// Evolves a bot population by having every individual play headless games of asteroids. Each
+/// generation plays the same rounds so that individuals are compared fairly.
+pub fn run_training(options: &Options) {
+    train(options, |_, _| {});
+}
+
+/// Like [`run_training`], but hands every generation to `on_generation` once it has been scored,
+/// along with the best individual so far.
+pub fn train(options: &Options, mut on_generation: impl FnMut(&Bot, &Individual)) {
+    let seed = options.seed.unwrap_or_else(|| thread_rng().gen());
+    let bot = match &options.resume {
+        Some(path) => resume(path.clone().or_else(checkpoint_path), options, seed),
+        None => bot_config(options).build(seed),
+    };
+    let mut bot = match bot {
+        Ok(bot) => bot,
+        Err(error) => {
+            eprintln!("There was an error setting up training: {error:?}");
+            return;
+        }
+    };
+    // a resumed run has to beat the champion it left off with before replacing it
+    let mut champion = options.resume.is_some().then(load_champion).flatten();
+
+    if let Some(directory) = bot_directory() {
+        if let Err(error) = std::fs::create_dir_all(&directory) {
+            eprintln!("There was an error creating the bot directory: {error:?}");
+        }
+
+        bot.log_metrics(MetricsLog::new(
+            directory.join(METRICS_FILE),
+            options
+                .metrics_json_lines
+                .then(|| directory.join(METRICS_JSON_LINES_FILE)),
+        ));
+    }
+
+    println!("Training with seed {seed}");
+
+    for _ in 0..options.generations {
+        let round_seeds: Vec<u64> = (0..ROUNDS_PER_INDIVIDUAL)
+            .map(|round| seed.wrapping_add(bot.generation_count * ROUNDS_PER_INDIVIDUAL + round))
+            .collect();
+
+        let fitness = bot.config.fitness;
+
+        // every round is its own app with its own seeded GameRng, so individuals can play on
+        // separate threads and still score the same as they would one after another
+        bot.evaluate(|individual| {
+            let total_score: f32 = round_seeds
+                .iter()
+                .map(|&round_seed| {
+                    fitness.fitness(&play_round(individual, round_seed, options.tick_rate))
+                })
+                .sum();
+
+            total_score / ROUNDS_PER_INDIVIDUAL as f32
+        });
+
+        print_generation(&bot);
+        save_champion(&bot, &mut champion);
+        if let Some(champion) = &champion {
+            on_generation(&bot, champion);
+        }
+        if let Err(error) = bot.run() {
+            eprintln!("There was an error recording training metrics: {error:?}");
+        }
+        save_checkpoint(&bot);
+    }
+}
+
+fn bot_config(options: &Options) -> BotConfig {
+    let mut config = BotConfig::new();
+
+    if let Some(population_size) = options.population_size {
+        config = config.population_size(population_size);
+    }
+
+    let mut selection_strategy = match options.selection.unwrap_or(Selection::Truncation) {
+        Selection::Truncation => SelectionStrategy::Truncation,
+        Selection::Tournament => SelectionStrategy::Tournament {
+            size: options.tournament_size.unwrap_or(DEFAULT_TOURNAMENT_SIZE),
+        },
+        Selection::Roulette => SelectionStrategy::Roulette,
+        Selection::Rank => SelectionStrategy::Rank,
+    };
+
+    if let Some(elite_count) = options.elites.filter(|&elites| elites > 0) {
+        selection_strategy = SelectionStrategy::Elitism {
+            elite_count,
+            then: Box::new(selection_strategy),
+        };
+    }
+
+    let crossover = match options.crossover.unwrap_or(CrossoverArg::SinglePoint) {
+        CrossoverArg::SinglePoint => CrossoverOperator::SinglePoint,
+        CrossoverArg::Uniform => CrossoverOperator::Uniform,
+        CrossoverArg::Blend => CrossoverOperator::Blend,
+        CrossoverArg::FitnessWeighted => CrossoverOperator::FitnessWeighted,
+    };
+
+    config
+        .selection_strategy(selection_strategy)
+        .crossover(crossover)
+}
+
+fn resume(path: Option<PathBuf>, options: &Options, seed: u64) -> eyre::Result<Bot> {
+    let Some(path) = path else {
+        eprintln!("There is no data directory to resume training from, starting over");
+        return bot_config(options).build(seed);
+    };
+
+    match Bot::load(&path) {
+        Ok(bot) => {
+            println!(
+                "Resuming training at generation {} from {}",
+                bot.generation_count,
+                path.display()
+            );
+
+            let ignored = options.population_flags();
+            if !ignored.is_empty() {
+                eprintln!(
+                    "Ignoring {} when resuming, the checkpoint's settings are kept",
+                    ignored.join(", ")
+                );
+            }
+
+            Ok(bot)
+        }
+        Err(error) => {
+            eprintln!(
+                "There was an error loading {}, starting over: {error:?}",
+                path.display()
+            );
+            bot_config(options).build(seed)
+        }
+    }
+}
+
+fn load_champion() -> Option<Individual> {
+    let path = champion_path().filter(|path| path.exists())?;
+
+    match Individual::load(&path) {
+        Ok(champion) => Some(champion),
+        Err(error) => {
+            eprintln!(
+                "There was an error loading {}, it will be replaced: {error:?}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Keeps the best individual seen during this run on disk for the bot pilot to fly.
+fn save_champion(bot: &Bot, champion: &mut Option<Individual>) {
+    let Some(best) = bot
+        .population
+        .iter()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+    else {
+        return;
+    };
+
+    if champion
+        .as_ref()
+        .is_some_and(|champion| champion.score >= best.score)
+    {
+        return;
+    }
+
+    *champion = Some(best.clone());
+
+    if let Some(path) = champion_path() {
+        save(&path, |path| best.save(path));
+    }
+}
+
+fn save_checkpoint(bot: &Bot) {
+    if let Some(path) = checkpoint_path() {
+        save(&path, |path| bot.save(path));
+    }
+}
+
+fn save(path: &Path, save: impl FnOnce(&Path) -> eyre::Result<()>) {
+    if let Err(error) = save(path) {
+        eprintln!("There was an error saving {}: {error:?}", path.display());
+    }
+}
+
+/// Plays one game with `individual` flying the ship and returns how it went.
+fn play_round(individual: &Individual, seed: u64, tick_rate: f64) -> PlayStats {
+    let mut pilot = individual.clone();
+    pilot.stats = PlayStats::default();
+
+    let mut app = App::new();
+
+    app.add_plugins((MinimalPlugins, InputPlugin, SimulationPlugin { tick_rate }))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / tick_rate,
+        )))
+        .insert_resource(GameRng::new(seed))
+        .insert_resource(BotPilot(pilot))
+        .add_systems(
+            OnEnter(GameState::Starting),
+            systems::headless_systems::skip_title_screen,
+        );
+
+    app.finish();
+    app.cleanup();
+
+    let max_ticks = (MAX_ROUND_SECONDS * tick_rate) as u64;
+
+    for _ in 0..max_ticks {
+        app.update();
+
+        if *app.world.resource::<State<GameState>>().get() == GameState::GameOver {
+            break;
+        }
+    }
+
+    app.world.resource::<BotPilot>().stats
+}
+
+fn print_generation(bot: &Bot) {
+    let scores: Vec<f32> = bot
+        .population
+        .iter()
+        .map(|individual| individual.score)
+        .collect();
+    let best = scores.iter().copied().fold(f32::MIN, f32::max);
+    let worst = scores.iter().copied().fold(f32::MAX, f32::min);
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+
+    println!(
+        "generation {}: best {best:.3} mean {mean:.3} worst {worst:.3}",
+        bot.generation_count
+    );
+}